@@ -0,0 +1,63 @@
+/// the bytes of a string value: hex when it starts with `hex:` or `0x`, base64 otherwise,
+/// with an optional `base64:` prefix. base64 can use the standard or the url-safe alphabet, with or without padding.
+pub(crate) fn decode_bytes(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix("hex:").or_else(|| s.strip_prefix("0x")) {
+        return decode_hex(hex);
+    }
+    decode_base64(s.strip_prefix("base64:").unwrap_or(s))
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    // an odd length leaves a last pair of one digit, which s.get rejects, and from_str_radix alone would accept a sign, e.g. "+1"
+    let pairs = (0..s.len()).step_by(2).map(|i| s.get(i..i + 2).filter(|pair| pair.bytes().all(|b| b.is_ascii_hexdigit())));
+    pairs.map(|pair| pair.and_then(|pair| u8::from_str_radix(pair, 16).ok())).collect()
+}
+
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(sextet);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // a single character left over cannot encode a byte
+    if bits >= 6 {
+        return None;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_and_base64_are_decoded() {
+        assert_eq!(Some(b"hello".to_vec()), decode_bytes("aGVsbG8="));
+        assert_eq!(Some(b"hello".to_vec()), decode_bytes("base64:aGVsbG8"));
+        assert_eq!(Some(vec![0xfb, 0xff]), decode_bytes("-_8"));
+        assert_eq!(Some(vec![0xde, 0xad, 0xbe, 0xef]), decode_bytes("0xDEADbeef"));
+        assert_eq!(Some(vec![0x01]), decode_bytes("hex:01"));
+        assert_eq!(None, decode_bytes("hex:123"));
+        assert_eq!(None, decode_bytes("hex:+1"));
+        assert_eq!(None, decode_bytes("0x-1"));
+        assert_eq!(None, decode_bytes("not base64!"));
+        assert_eq!(None, decode_bytes("aGVsbG8xx"));
+    }
+}
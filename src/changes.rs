@@ -0,0 +1,29 @@
+//! a tokio watch channel of the configuration, for async services.
+
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+use tokio::sync::watch;
+
+static CHANNEL: Lazy<watch::Sender<Arc<Map<String, Value>>>> = Lazy::new(|| watch::channel(crate::CONFIGS.load_full()).0);
+
+/// Watch the whole configuration from async code, e.g. to `select!` on its updates alongside other event sources.
+/// the receiver holds the configuration of when it was created, and `changed().await` resolves every time
+/// a new one is loaded by read_config, set or a watched source. needs the `tokio` feature.
+/// # Example
+/// ```
+/// let mut changes = confmap::changes();
+/// confmap::set("changesExample.workers", 4).unwrap();
+/// assert!(changes.has_changed().unwrap());
+/// let config = changes.borrow_and_update().clone();
+/// assert_eq!(Some(4), config["changesExample"]["workers"].as_i64());
+/// ```
+pub fn changes() -> watch::Receiver<Arc<Map<String, Value>>> {
+    CHANNEL.subscribe()
+}
+
+/// sends a newly published configuration to the receivers.
+pub(crate) fn publish(values: &Arc<Map<String, Value>>) {
+    CHANNEL.send_replace(values.clone());
+}
@@ -0,0 +1,165 @@
+//! the conversions of the typed getters, strict by default, see set_coercion and ConfigBuilder::coercion.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::Value;
+
+use crate::freeze::{check, FrozenError};
+use crate::getters::ReadOptions;
+use crate::keys::KeyMode;
+
+static COERCION: AtomicBool = AtomicBool::new(false);
+
+/// Let the typed getters convert between strings, numbers and bools, like viper does.
+/// get_string then gives the text of a number or a bool, get_int64 and get_float64 parse strings such as `"42"`,
+/// and get_bool accepts `"true"`, `"yes"`, `"on"`, `"1"`, their opposites, and numbers.
+/// it helps with environment variables and properties files, where every value is a string.
+/// it applies to the process-wide configuration and its views, a Config sets it with ConfigBuilder::coercion.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set_coercion(true).unwrap();
+/// confmap::read_config();
+/// ```
+pub fn set_coercion(enabled: bool) -> Result<(), FrozenError> {
+    check()?;
+    COERCION.store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+/// what the array getters do with the elements which are not of the requested type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MixedArrays {
+    /// the other elements are left out, this is the default.
+    #[default]
+    Skip,
+    /// the whole array gives None.
+    Reject,
+}
+
+static REJECT_MIXED_ARRAYS: AtomicBool = AtomicBool::new(false);
+
+/// Set what the array getters, such as get_int64_array, do with the elements which are not of the requested type,
+/// and what the map getters, such as get_string_map, do with such values.
+/// it applies to the process-wide configuration and its views, a Config sets it with ConfigBuilder::mixed_arrays.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// // ["1", 2] is an error in the config, not [2]
+/// confmap::set_mixed_arrays(confmap::MixedArrays::Reject).unwrap();
+/// ```
+pub fn set_mixed_arrays(policy: MixedArrays) -> Result<(), FrozenError> {
+    check()?;
+    REJECT_MIXED_ARRAYS.store(policy == MixedArrays::Reject, Ordering::SeqCst);
+    Ok(())
+}
+
+impl ReadOptions {
+    /// the conversions set for the process-wide configuration by set_coercion and set_mixed_arrays, with its keys.
+    pub(crate) fn process() -> ReadOptions {
+        let mixed_arrays = if REJECT_MIXED_ARRAYS.load(Ordering::SeqCst) { MixedArrays::Reject } else { MixedArrays::Skip };
+        ReadOptions { coercion: COERCION.load(Ordering::SeqCst), mixed_arrays, keys: KeyMode::Process }
+    }
+
+    /// the elements of an array converted by `convert`, the others skipped or rejected as set by mixed_arrays.
+    pub(crate) fn to_array<T>(self, value: Value, convert: impl Fn(&Value) -> Option<T>) -> Option<Vec<T>> {
+        let Value::Array(arr) = value else {
+            return None;
+        };
+        let reject = self.mixed_arrays == MixedArrays::Reject;
+        let mut converted = Vec::with_capacity(arr.len());
+        for element in &arr {
+            match convert(element) {
+                Some(element) => converted.push(element),
+                None if reject => return None,
+                None => {}
+            }
+        }
+        Some(converted)
+    }
+
+    pub(crate) fn to_string(self, value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(_) | Value::Bool(_) if self.coercion => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_i64(self, value: &Value) -> Option<i64> {
+        match value {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) if self.coercion => s.trim().parse().ok(),
+            Value::Bool(b) if self.coercion => Some(i64::from(*b)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_u64(self, value: &Value) -> Option<u64> {
+        match value {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) if self.coercion => s.trim().parse().ok(),
+            Value::Bool(b) if self.coercion => Some(u64::from(*b)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_f64(self, value: &Value) -> Option<f64> {
+        match value {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) if self.coercion => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_bool(self, value: &Value) -> Option<bool> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            Value::String(s) if self.coercion => parse_bool(s),
+            Value::Number(n) if self.coercion => n.as_f64().map(|n| n != 0.0),
+            _ => None,
+        }
+    }
+
+    /// the values of an object converted by `convert`, the others skipped or rejected like the elements of an array.
+    pub(crate) fn to_map<T>(self, value: Value, convert: impl Fn(&Value) -> Option<T>) -> Option<HashMap<String, T>> {
+        let Value::Object(map) = value else {
+            return None;
+        };
+        let reject = self.mixed_arrays == MixedArrays::Reject;
+        let mut converted = HashMap::with_capacity(map.len());
+        for (key, value) in &map {
+            match convert(value) {
+                Some(value) => {
+                    converted.insert(key.clone(), value);
+                }
+                None if reject => return None,
+                None => {}
+            }
+        }
+        Some(converted)
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bools_are_parsed_from_common_words() {
+        assert_eq!(Some(true), parse_bool("Yes"));
+        assert_eq!(Some(true), parse_bool(" on "));
+        assert_eq!(Some(false), parse_bool("OFF"));
+        assert_eq!(Some(false), parse_bool("0"));
+        assert_eq!(None, parse_bool("maybe"));
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{btree_map, BTreeMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use crate::coerce::MixedArrays;
+use crate::diff::flatten;
+use crate::getters::ReadOptions;
+use crate::keys::{normalize_map_with, KeyMode};
+use crate::layer::Loaded;
+use crate::pattern::lookup_key;
+use crate::query::{flatten_prefix, matching, Entries};
+use crate::schema::check_inline_types;
+use crate::{ConfigRead, EnvSource, FileSource, Format, MergeStrategy, Source, SourceError};
+
+/// a configuration loaded by a ConfigBuilder or built from a file or a value, independent of the process-wide one of read_config.
+/// nothing set for the process-wide configuration applies to it, so a library can keep its own configuration
+/// without clobbering the one of the application or of another library.
+/// cloning a Config is cheap, the values are shared, so it can be kept in the state of a web app or moved into tasks.
+/// # Example
+/// ```no_run
+/// use confmap::{ConfigBuilder, ConfigRead};
+///
+/// let config = ConfigBuilder::new().name("config").path("/etc/myapp").path(".").env_prefix("MYAPP").build()?;
+/// let port = config.get_int64("port");
+/// # Ok::<(), confmap::SourceError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Config {
+    values: Arc<Map<String, Value>>,
+    options: ReadOptions,
+}
+
+impl Config {
+    /// a configuration holding the keys of a map.
+    pub fn from_map(values: Map<String, Value>) -> Config {
+        Config::with_options(values, ReadOptions::default())
+    }
+
+    pub(crate) fn with_options(values: Map<String, Value>, options: ReadOptions) -> Config {
+        Config { values: Arc::new(values), options }
+    }
+
+    /// a configuration holding the keys of a json object, e.g. the settings given by the caller of a library.
+    /// fails when the value is not an object.
+    /// # Example
+    /// ```
+    /// use confmap::{Config, ConfigRead};
+    ///
+    /// let config = Config::from_value(serde_json::json!({"retries": 3})).unwrap();
+    /// assert_eq!(Some(3), config.get_int64("retries"));
+    /// ```
+    pub fn from_value(value: Value) -> Result<Config, SourceError> {
+        match value {
+            Value::Object(values) => Ok(Config::from_map(values)),
+            other => Err(SourceError::Parse(format!("a config must be an object, found {}", other))),
+        }
+    }
+
+    /// the whole configuration.
+    pub fn values(&self) -> &Map<String, Value> {
+        &self.values
+    }
+
+    /// an iterator over the top-level `(key, value)` pairs, see confmap::iter.
+    pub fn iter(&self) -> Entries {
+        Entries::new(self.values.clone())
+    }
+
+    /// an iterator over the `(dotted key, value)` pairs of every value, see confmap::iter_flat.
+    pub fn iter_flat(&self) -> btree_map::IntoIter<String, Value> {
+        flatten(&self.values).into_iter()
+    }
+
+    /// every value under a prefix by dotted key, as strings, see confmap::flatten.
+    pub fn flatten(&self, prefix: &str) -> BTreeMap<String, String> {
+        flatten_prefix(&self.values, prefix)
+    }
+
+    /// the dotted key of every value, see confmap::keys.
+    pub fn keys(&self) -> Vec<String> {
+        flatten(&self.values).into_keys().collect()
+    }
+
+    /// this function will return every value whose dotted key matches a pattern, see confmap::get_matching.
+    pub fn get_matching(&self, pattern: &str) -> Vec<(String, Value)> {
+        matching(&self.values, pattern)
+    }
+
+    /// a configuration read from a file, the format is guessed from its extension.
+    /// # Example
+    /// ```no_run
+    /// let config = confmap::Config::from_file("mylib.json")?;
+    /// # Ok::<(), confmap::SourceError>(())
+    /// ```
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Config, SourceError> {
+        let mut loaded = Loaded::default();
+        push(&mut loaded, &FileSource::new(path), false)?;
+        Ok(Config::from_map(loaded.standalone_values()))
+    }
+}
+
+impl IntoIterator for &Config {
+    type Item = (String, Value);
+    type IntoIter = Entries;
+
+    fn into_iter(self) -> Entries {
+        self.iter()
+    }
+}
+
+impl ConfigRead for Config {
+    fn get(&self, key: &str) -> Option<Value> {
+        lookup_key(&self.values, &self.options.keys.normalize(key)).cloned()
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        self.options
+    }
+}
+
+/// the setup of a Config, in any order.
+/// the config file is looked up in the paths in the order they are added, the first one found is read,
+/// then the environment variables with the prefix are merged over it.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    name: Option<String>,
+    paths: Vec<PathBuf>,
+    format: Option<Format>,
+    env_prefix: Option<String>,
+    case_insensitive: bool,
+    options: ReadOptions,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// the name of the config file, without an extension every extension of the format is tried: `config` finds `config.json`.
+    pub fn name(mut self, name: &str) -> ConfigBuilder {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// a folder to look for the config file in, the current folder when none is added.
+    pub fn path(mut self, path: impl Into<PathBuf>) -> ConfigBuilder {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// read the config file as this format whatever its extension is.
+    pub fn format(mut self, format: Format) -> ConfigBuilder {
+        self.format = Some(format);
+        self
+    }
+
+    /// merge the environment variables with this prefix, see EnvSource.
+    pub fn env_prefix(mut self, prefix: &str) -> ConfigBuilder {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// lowercase the keys of the config file and the environment variables, and the keys given to the getters.
+    pub fn case_insensitive(mut self, enabled: bool) -> ConfigBuilder {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// let the typed getters convert between strings, numbers and bools, like set_coercion does for the process-wide configuration.
+    pub fn coercion(mut self, enabled: bool) -> ConfigBuilder {
+        self.options.coercion = enabled;
+        self
+    }
+
+    /// what the array and map getters do with the values of another type, like set_mixed_arrays does for the process-wide configuration.
+    pub fn mixed_arrays(mut self, policy: MixedArrays) -> ConfigBuilder {
+        self.options.mixed_arrays = policy;
+        self
+    }
+
+    /// loads the configuration, failing when the config file is not found or a source cannot be read.
+    pub fn build(self) -> Result<Config, SourceError> {
+        let mut loaded = Loaded::default();
+        if let Some(name) = &self.name {
+            let file = self.find_file(name)?;
+            let mut source = FileSource::new(&file);
+            if let Some(format) = self.format {
+                source = source.format(format);
+            }
+            push(&mut loaded, &source, self.case_insensitive)?;
+        }
+        if let Some(prefix) = &self.env_prefix {
+            push(&mut loaded, &EnvSource::new(prefix), self.case_insensitive)?;
+        }
+        let mut options = self.options;
+        if self.case_insensitive {
+            options.keys = KeyMode::Lowercase;
+        }
+        Ok(Config::with_options(loaded.standalone_values(), options))
+    }
+
+    fn find_file(&self, name: &str) -> Result<PathBuf, SourceError> {
+        let default_paths = [PathBuf::from(".")];
+        let paths = if self.paths.is_empty() { &default_paths[..] } else { &self.paths[..] };
+        let names: Vec<String> = if Path::new(name).extension().is_some() {
+            vec![name.to_string()]
+        } else {
+            let formats = match self.format {
+                Some(format) => vec![format],
+                None => Format::all().to_vec(),
+            };
+            formats.iter().flat_map(|format| format.extensions()).map(|extension| format!("{}.{}", name, extension)).collect()
+        };
+        paths
+            .iter()
+            .flat_map(|path| names.iter().map(move |name| path.join(name)))
+            .find(|file| file.is_file())
+            .ok_or_else(|| {
+                let searched: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+                let message = format!("config file {} not found in {}", name, searched.join(", "));
+                SourceError::Io(io::Error::new(io::ErrorKind::NotFound, message))
+            })
+    }
+}
+
+fn push(loaded: &mut Loaded, source: &dyn Source, case_insensitive: bool) -> Result<(), SourceError> {
+    let mut values = normalize_map_with(source.load()?, case_insensitive);
+    check_inline_types(&mut values)?;
+    loaded.push(source.layer(), &source.name(), values, MergeStrategy::DeepMerge);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_first_file_found_is_read() {
+        let dir = std::env::temp_dir().join(format!("confmap_builder_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("etc")).unwrap();
+        std::fs::write(dir.join("etc/app.json"), r#"{"port": 8080, "Server": {"Host": "h"}}"#).unwrap();
+        let config = ConfigBuilder::new().name("app").path(dir.join("missing")).path(dir.join("etc")).build().unwrap();
+        assert_eq!(Some(8080), config.get_int64("port"));
+        assert_eq!(None, config.get_string("server.host"));
+        let config = ConfigBuilder::new().name("app").path(dir.join("etc")).case_insensitive(true).build().unwrap();
+        assert_eq!(Some("h".to_string()), config.get_string("SERVER.host"));
+        assert_eq!(Some("h".to_string()), config.get_map("Server").and_then(|server| server.get_string("HOST")));
+        assert_eq!(Some("h".to_string()), config.sub("Server").and_then(|server| server.get_string("HOST")));
+        let missing = ConfigBuilder::new().name("other").path(dir.join("etc")).build();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(missing, Err(SourceError::Io(e)) if e.kind() == io::ErrorKind::NotFound));
+        assert!(Config::from_value(Value::from(1)).is_err());
+        assert!(Arc::ptr_eq(&config.values, &config.clone().values));
+        assert_eq!(vec!["port", "server.host"], config.keys());
+    }
+}
@@ -0,0 +1,87 @@
+use std::panic;
+
+use serde_json::{Map, Value};
+
+use crate::pattern::{lookup_path, split_path};
+use crate::redact::redact_map;
+use crate::CONFIGS;
+
+/// this function will return a short hash of the whole configuration, which changes whenever any value changes.
+/// two processes with the same fingerprint run with the same configuration.
+/// # Example
+/// ```
+/// confmap::read_config();
+/// println!("config fingerprint {}", confmap::fingerprint());
+/// ```
+pub fn fingerprint() -> String {
+    fingerprint_of(&CONFIGS.load())
+}
+
+/// this function will return the fingerprint and the given keys (secrets masked) as `(name, value)` pairs,
+/// the names are prefixed with `config.`, ready to be set as tags of a crash reporter scope, e.g. with sentry:
+/// # Example
+/// ```
+/// for (name, value) in confmap::crash_context(&["server.port", "db.host"]) {
+///     // sentry::configure_scope(|scope| scope.set_tag(&name, value));
+///     println!("{}={}", name, value);
+/// }
+/// ```
+pub fn crash_context(keys: &[&str]) -> Vec<(String, String)> {
+    context_of(&CONFIGS.load(), keys)
+}
+
+/// Print the crash context of the given keys when the program panics, before the previous panic hook runs.
+/// the context is the one of the configuration at the time of the panic.
+/// # Example
+/// ```
+/// confmap::install_panic_hook(&["server.port", "db.host"]);
+/// ```
+pub fn install_panic_hook(keys: &[&str]) {
+    let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        for (name, value) in context_of(&CONFIGS.load(), &keys) {
+            eprintln!("{}={}", name, value);
+        }
+        previous(info);
+    }));
+}
+
+fn context_of(configs: &Map<String, Value>, keys: &[&str]) -> Vec<(String, String)> {
+    let masked = redact_map(configs);
+    let mut context = vec![("config.fingerprint".to_string(), fingerprint_of(configs))];
+    for key in keys {
+        let value = match lookup_path(&masked, &split_path(key)) {
+            None => "<unset>".to_string(),
+            Some(Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        };
+        context.push((format!("config.{}", key), value));
+    }
+    context
+}
+
+/// FNV-1a of the json text, which is stable across builds unlike the std hashers.
+fn fingerprint_of(configs: &Map<String, Value>) -> String {
+    let text = serde_json::to_string(configs).unwrap_or_default();
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_has_the_fingerprint_and_the_keys() {
+        let configs: Map<String, Value> = serde_json::from_str(r#"{"server": {"port": 8080}, "name": "api"}"#).unwrap();
+        let context = context_of(&configs, &["server.port", "name", "missing"]);
+        assert_eq!(("config.fingerprint".to_string(), fingerprint_of(&configs)), context[0]);
+        assert_eq!(("config.server.port".to_string(), "8080".to_string()), context[1]);
+        assert_eq!(("config.name".to_string(), "api".to_string()), context[2]);
+        assert_eq!(("config.missing".to_string(), "<unset>".to_string()), context[3]);
+        let other: Map<String, Value> = serde_json::from_str(r#"{"server": {"port": 8081}, "name": "api"}"#).unwrap();
+        assert_ne!(fingerprint_of(&configs), fingerprint_of(&other));
+    }
+}
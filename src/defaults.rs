@@ -0,0 +1,197 @@
+//! the default values registered by the application, below every source.
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::freeze::{check, FrozenError};
+use crate::keys::normalize_map;
+use crate::layer::Loaded;
+use crate::merge::deep_merge;
+use crate::pattern::{insert_path, split_path};
+#[cfg(feature = "toml")]
+use crate::metadata::all_metadata;
+use crate::write::write_atomic;
+use crate::{Format, Layer, MergeStrategy};
+
+/// the defaults set by set_default and set_defaults_from.
+static DEFAULTS: Lazy<Mutex<Map<String, Value>>> = Lazy::new(|| Mutex::new(Map::new()));
+
+const DEFAULTS_ORIGIN: &str = "defaults";
+
+/// the error returned by set_defaults_from and set_defaults_from_struct.
+#[derive(Debug)]
+pub enum DefaultsError {
+    /// the configuration is frozen.
+    Frozen,
+    /// the defaults are not an object, so they have no keys.
+    NotAnObject,
+    /// the struct does not serialize, e.g. a map whose keys are not strings.
+    Serialize(serde_json::Error),
+}
+
+impl fmt::Display for DefaultsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultsError::Frozen => write!(f, "{}", FrozenError),
+            DefaultsError::NotAnObject => write!(f, "the defaults must be an object"),
+            DefaultsError::Serialize(e) => write!(f, "failed to serialize the defaults: {}", e),
+        }
+    }
+}
+
+impl Error for DefaultsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DefaultsError::Serialize(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FrozenError> for DefaultsError {
+    fn from(_: FrozenError) -> Self {
+        DefaultsError::Frozen
+    }
+}
+
+/// Set the default value of a dotted key, like viper's SetDefault.
+/// a default belongs to the Default layer and only gives a value to a key no file or source sets,
+/// so a file setting `server.host` keeps the default of `server.port`. fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set_default("server.port", 8080).unwrap();
+/// assert_eq!(Some(8080), confmap::get_int64("server.port"));
+/// ```
+pub fn set_default(key: &str, value: impl Into<Value>) -> Result<(), FrozenError> {
+    let mut map = Map::new();
+    insert_path(&mut map, &split_path(key), value.into());
+    add_defaults(map)
+}
+
+/// Set many defaults at once from an object, e.g. a json document embedded in the binary.
+/// the nested objects are merged key by key with the defaults set before. fails when the value is not an object,
+/// or once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set_defaults_from(serde_json::json!({"server": {"port": 8080, "host": "0.0.0.0"}})).unwrap();
+/// ```
+pub fn set_defaults_from(defaults: Value) -> Result<(), DefaultsError> {
+    match defaults {
+        Value::Object(map) => Ok(add_defaults(map)?),
+        _ => Err(DefaultsError::NotAnObject),
+    }
+}
+
+/// Set the defaults from a struct, e.g. the `Settings::default()` of the application, serialized with serde.
+/// the fields become the keys, the nested structs sections. fails when it does not serialize to an object, see DefaultsError.
+/// # Example
+/// ```
+/// #[derive(serde::Serialize, Default)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// confmap::set_defaults_from_struct(&Server { host: "0.0.0.0".to_string(), port: 8080 }).unwrap();
+/// assert_eq!(Some(8080), confmap::get_int64("port"));
+/// ```
+pub fn set_defaults_from_struct<T: Serialize>(defaults: &T) -> Result<(), DefaultsError> {
+    set_defaults_from(serde_json::to_value(defaults).map_err(DefaultsError::Serialize)?)
+}
+
+/// Write a config file holding every default, e.g. for a `--init-config` option scaffolding the file of a new user.
+/// in toml the description set by set_key_metadata is written as a comment above its key, json has no comments.
+/// # Example
+/// ```no_run
+/// confmap::set_default("server.port", 8080).unwrap();
+/// confmap::write_default_config("config.json", confmap::Format::Json).expect("failed to write the config file");
+/// ```
+pub fn write_default_config(path: impl AsRef<Path>, format: Format) -> Result<(), Box<dyn Error>> {
+    let defaults = DEFAULTS.lock().unwrap().clone();
+    let document = format.serialize(&defaults)?;
+    let document = match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => annotate_toml(&document),
+        _ => document,
+    };
+    write_atomic(path.as_ref(), document.as_bytes(), true)?;
+    Ok(())
+}
+
+/// adds the description of every key above it, as a comment.
+#[cfg(feature = "toml")]
+fn annotate_toml(document: &str) -> String {
+    let metadata = all_metadata();
+    let description = |key: &str| metadata.get(key).and_then(|metadata| metadata.description.clone());
+    let mut annotated = String::new();
+    let mut table = String::new();
+    for line in document.lines() {
+        let trimmed = line.trim();
+        let key = if let Some(header) = trimmed.strip_prefix('[').and_then(|header| header.strip_suffix(']')).filter(|header| !header.starts_with('[')) {
+            table = header.replace('"', "");
+            Some(table.clone())
+        } else if trimmed.starts_with("[[") {
+            table.clear();
+            None
+        } else {
+            trimmed.split_once('=').map(|(key, _)| {
+                let key = key.trim().replace('"', "");
+                if table.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", table, key)
+                }
+            })
+        };
+        if let Some(description) = key.as_deref().and_then(description) {
+            for comment in description.lines() {
+                annotated.push_str(&format!("# {}\n", comment));
+            }
+        }
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+    annotated
+}
+
+fn add_defaults(defaults: Map<String, Value>) -> Result<(), FrozenError> {
+    check()?;
+    deep_merge(&mut DEFAULTS.lock().unwrap(), normalize_map(defaults));
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// adds the defaults below the sources being loaded, replacing the ones added before.
+pub(crate) fn read_defaults(configs: &mut Loaded) {
+    configs.remove(Layer::Default, DEFAULTS_ORIGIN);
+    let defaults = DEFAULTS.lock().unwrap().clone();
+    if !defaults.is_empty() {
+        configs.push(Layer::Default, DEFAULTS_ORIGIN, defaults, MergeStrategy::DeepMerge);
+    }
+}
+
+/// forgets every default, see reset.
+pub(crate) fn clear() {
+    DEFAULTS.lock().unwrap().clear();
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod tests {
+    use super::*;
+    use crate::{set_key_metadata, KeyMetadata};
+
+    #[test]
+    fn descriptions_become_toml_comments() {
+        set_key_metadata("defaultsTestServer.port", KeyMetadata::new().description("the port to listen on"));
+        set_key_metadata("defaultsTestServer", KeyMetadata::new().description("the http server"));
+        let document = "[defaultsTestServer]\nhost = \"0.0.0.0\"\nport = 8080\n";
+        let expected = "# the http server\n[defaultsTestServer]\nhost = \"0.0.0.0\"\n# the port to listen on\nport = 8080\n";
+        assert_eq!(expected, annotate_toml(document));
+    }
+}
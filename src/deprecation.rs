@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::freeze::{check, FrozenError};
+use crate::keys::{aliases, register_deprecated_alias};
+use crate::layer::Loaded;
+use crate::metadata::all_metadata;
+
+type DeprecationHandler = Arc<dyn Fn(&DeprecatedKey) + Send + Sync>;
+
+/// the deprecated keys, by key.
+static DEPRECATED: Lazy<Mutex<BTreeMap<String, DeprecatedKey>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+static HANDLER: Lazy<Mutex<Option<DeprecationHandler>>> = Lazy::new(|| Mutex::new(None));
+
+/// a key replaced by another one, see deprecate_key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedKey {
+    /// the deprecated dotted key.
+    pub key: String,
+    /// the key to use instead.
+    pub replacement: String,
+    /// when the key was deprecated, e.g. `since 2.0`.
+    pub since: String,
+}
+
+/// Deprecate a key in favor of a replacement.
+/// the deprecated key works like an alias of the replacement (see register_alias), and the first time it is used,
+/// by a getter or by a file or source setting it, a warning is printed, or given to the handler set by on_deprecated_key.
+/// fails once the configuration is frozen, like register_alias.
+/// # Example
+/// ```
+/// confmap::deprecate_key("db.addr", "database.host", "since 2.0").unwrap();
+/// ```
+pub fn deprecate_key(key: &str, replacement: &str, since: &str) -> Result<(), FrozenError> {
+    check()?;
+    let deprecated = DeprecatedKey { key: key.to_string(), replacement: replacement.to_string(), since: since.to_string() };
+    DEPRECATED.lock().unwrap().insert(key.to_string(), deprecated);
+    register_deprecated_alias(key, replacement)
+}
+
+/// Handle the warnings about deprecated keys instead of printing them, e.g. to log them as structured events.
+/// # Example
+/// ```
+/// confmap::on_deprecated_key(|deprecated| {
+///     println!("config_deprecated key={} replacement={} {}", deprecated.key, deprecated.replacement, deprecated.since);
+/// });
+/// ```
+pub fn on_deprecated_key<F>(handler: F)
+where
+    F: Fn(&DeprecatedKey) + Send + Sync + 'static,
+{
+    *HANDLER.lock().unwrap() = Some(Arc::new(handler));
+}
+
+/// warns that a deprecated key was used, the aliases call it the first time they are used.
+pub(crate) fn warn_deprecated(alias: &str) {
+    let deprecated = match DEPRECATED.lock().unwrap().get(alias) {
+        Some(deprecated) => deprecated.clone(),
+        None => return,
+    };
+    let handler = HANDLER.lock().unwrap().clone();
+    match handler {
+        Some(handler) => handler(&deprecated),
+        None => println!(
+            "deprecated config key {} ({}), use {} instead",
+            deprecated.key, deprecated.since, deprecated.replacement
+        ),
+    }
+}
+
+/// a deprecated key found in a loaded source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecationHit {
+    /// the dotted key.
+    pub key: String,
+    /// the name of the source which sets it, e.g. the path of the file.
+    pub origin: String,
+    /// the note given to KeyMetadata::deprecated.
+    pub note: String,
+}
+
+/// this function will return every deprecated key set by the sources read by read_config, with the source setting it.
+/// a key is deprecated by its metadata or by deprecate_key, an alias registered by register_alias is reported as well.
+/// keys set by a disabled layer are reported too.
+/// # Example
+/// ```
+/// use confmap::KeyMetadata;
+///
+/// confmap::set_key_metadata("verbose", KeyMetadata::new().deprecated("use logging.verbose instead"));
+/// confmap::read_config();
+/// for hit in confmap::deprecation_report() {
+///     println!("{} sets {}: {}", hit.origin, hit.key, hit.note);
+/// }
+/// ```
+pub fn deprecation_report() -> Vec<DeprecationHit> {
+    report(&Loaded::current())
+}
+
+fn report(loaded: &Loaded) -> Vec<DeprecationHit> {
+    let mut hits = Vec::new();
+    for (key, metadata) in all_metadata() {
+        if let Some(note) = metadata.deprecated {
+            for origin in loaded.origins_setting(&key) {
+                hits.push(DeprecationHit { key: key.clone(), origin, note: note.clone() });
+            }
+        }
+    }
+    let deprecated = DEPRECATED.lock().unwrap().clone();
+    for (alias, key) in aliases() {
+        let note = match deprecated.get(&alias) {
+            Some(deprecated) => format!("deprecated {}, use {} instead", deprecated.since, key),
+            None => format!("alias of {}", key),
+        };
+        for origin in loaded.origins_setting(&alias) {
+            hits.push(DeprecationHit { key: alias.clone(), origin, note: note.clone() });
+        }
+    }
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_key_metadata, KeyMetadata, Layer, MergeStrategy};
+
+    #[test]
+    fn deprecated_keys_are_reported_per_source() {
+        set_key_metadata("deprecation_test.old", KeyMetadata::new().deprecated("use deprecation_test.new"));
+        let mut loaded = Loaded::default();
+        loaded.push(Layer::File, "a.json", serde_json::from_str(r#"{"deprecation_test": {"old": 1}}"#).unwrap(), MergeStrategy::Replace);
+        loaded.push(Layer::File, "b.json", serde_json::from_str(r#"{"deprecation_test": {"new": 1}}"#).unwrap(), MergeStrategy::Replace);
+        loaded.push(Layer::Env, "env://APP", serde_json::from_str(r#"{"deprecation_test": {"old": 2}}"#).unwrap(), MergeStrategy::Replace);
+        let origins: Vec<String> = report(&loaded).into_iter().map(|hit| hit.origin).collect();
+        assert_eq!(vec!["a.json", "env://APP"], origins);
+    }
+
+    #[test]
+    fn deprecated_keys_warn_once() {
+        let _guard = crate::tests::lock_global_state();
+        let previous = HANDLER.lock().unwrap().take();
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let seen = warnings.clone();
+        on_deprecated_key(move |deprecated| seen.lock().unwrap().push(deprecated.key.clone()));
+        deprecate_key("deprecationTestAddr", "deprecationTestHost", "since 2.0").unwrap();
+        assert_eq!("deprecationTestHost", crate::keys::normalize_key("deprecationTestAddr"));
+        crate::keys::normalize_key("deprecationTestAddr");
+        *HANDLER.lock().unwrap() = previous;
+        assert_eq!(vec!["deprecationTestAddr"], *warnings.lock().unwrap());
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+/// every leaf of a map by its dotted key, an array is a leaf and an empty object too.
+pub(crate) fn flatten(map: &Map<String, Value>) -> BTreeMap<String, Value> {
+    let mut leaves = BTreeMap::new();
+    flatten_into(map, "", &mut leaves);
+    leaves
+}
+
+fn flatten_into(map: &Map<String, Value>, prefix: &str, leaves: &mut BTreeMap<String, Value>) {
+    for (key, value) in map {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Object(child) if !child.is_empty() => flatten_into(child, &path, leaves),
+            _ => {
+                leaves.insert(path, value.clone());
+            }
+        }
+    }
+}
+
+/// the leaf keys a reload added, removed or modified, see reload.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// the dotted keys which were not set before.
+    pub added: Vec<String>,
+    /// the dotted keys which are not set anymore.
+    pub removed: Vec<String>,
+    /// the dotted keys whose value changed.
+    pub changed: Vec<String>,
+}
+
+impl ConfigDiff {
+    /// the leaf keys which differ between two maps, each list sorted.
+    pub(crate) fn between(old: &Map<String, Value>, new: &Map<String, Value>) -> ConfigDiff {
+        let (old, new) = (flatten(old), flatten(new));
+        let mut diff = ConfigDiff::default();
+        for (key, value) in &old {
+            match new.get(key) {
+                None => diff.removed.push(key.clone()),
+                Some(new_value) if new_value != value => diff.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.added = new.keys().filter(|key| !old.contains_key(*key)).cloned().collect();
+        diff
+    }
+
+    /// whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// the dotted keys which were added, removed or modified between two maps.
+pub(crate) fn changed_keys(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<String> {
+    let (old, new) = (flatten(old), flatten(new));
+    let mut changed: Vec<String> = old
+        .iter()
+        .filter(|(key, value)| new.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    changed.extend(new.keys().filter(|key| !old.contains_key(*key)).cloned());
+    changed.sort();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_leaves_are_listed() {
+        let old: Map<String, Value> = serde_json::from_str(r#"{"a": 1, "db": {"host": "h", "port": 1}, "gone": true}"#).unwrap();
+        let new: Map<String, Value> = serde_json::from_str(r#"{"a": 1, "db": {"host": "h2", "port": 1}, "added": [1]}"#).unwrap();
+        assert_eq!(vec!["added", "db.host", "gone"], changed_keys(&old, &new));
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!((vec!["added".to_string()], vec!["gone".to_string()], vec!["db.host".to_string()]), (diff.added, diff.removed, diff.changed));
+    }
+}
@@ -0,0 +1,54 @@
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// the names of the variants of an enum deriving Deserialize, renames applied, None for any other type.
+pub(crate) fn variants_of<E: DeserializeOwned>() -> Option<&'static [&'static str]> {
+    let mut variants = None;
+    let _ = E::deserialize(VariantsOf(&mut variants));
+    variants
+}
+
+/// a deserializer which only records the variants an enum asks for.
+struct VariantsOf<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for VariantsOf<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not an enum"))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(variants);
+        Err(de::Error::custom("only the variants are recorded"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    #[allow(dead_code)]
+    enum Mode {
+        Fast,
+        SafeAndSlow,
+    }
+
+    #[test]
+    fn variants_are_recorded() {
+        assert_eq!(Some(&["fast", "safe_and_slow"][..]), variants_of::<Mode>());
+        assert_eq!(None, variants_of::<String>());
+    }
+}
@@ -0,0 +1,64 @@
+use std::env;
+
+use serde_json::{Map, Value};
+
+use crate::import::{insert_nested, parse_scalar};
+use crate::{Layer, Source, SourceError};
+
+/// the environment variables starting with a prefix, e.g. with the prefix `MYAPP`
+/// `MYAPP_PORT=8080` is the key `port` and `MYAPP_DATABASE__HOST=db` is `database.host`.
+/// names are lowercased, `__` separates nested sections and values are typed like in a dotenv file.
+#[derive(Debug, Clone)]
+pub struct EnvSource {
+    prefix: String,
+}
+
+impl EnvSource {
+    /// an empty prefix takes every environment variable.
+    pub fn new(prefix: &str) -> EnvSource {
+        EnvSource { prefix: prefix.to_string() }
+    }
+
+    fn key_of(&self, name: &str) -> Option<String> {
+        let key = if self.prefix.is_empty() {
+            name
+        } else {
+            name.strip_prefix(&self.prefix)?.strip_prefix('_')?
+        };
+        Some(key.to_lowercase()).filter(|key| !key.is_empty())
+    }
+}
+
+impl Source for EnvSource {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        let mut map = Map::new();
+        for (name, raw) in env::vars() {
+            if let Some(key) = self.key_of(&name) {
+                insert_nested(&mut map, &key.split("__").collect::<Vec<_>>(), parse_scalar(&raw));
+            }
+        }
+        Ok(map)
+    }
+
+    fn name(&self) -> String {
+        format!("env://{}", self.prefix)
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Env
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixed_variables_become_nested_keys() {
+        env::set_var("CONFMAP_ENV_TEST_DATABASE__PORT", "5432");
+        env::set_var("CONFMAP_ENV_TESTX", "ignored");
+        let map = EnvSource::new("CONFMAP_ENV_TEST").load().unwrap();
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"database": {"port": 5432}}"#).unwrap();
+        assert_eq!(expected, map);
+    }
+}
@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::layer::{LayerValue, Loaded};
+use crate::pattern::{lookup_path, split_path};
+use crate::redact::redact_map;
+use crate::Layer;
+
+/// where the effective value of a key came from and the values it shadows, see explain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// the dotted key.
+    pub key: String,
+    /// the effective value of the key, secrets masked.
+    pub value: Option<Value>,
+    /// the source of the effective value, none when no enabled source sets the key.
+    pub source: Option<KeySource>,
+    /// the other sources setting the key, from the highest precedence to the lowest.
+    pub shadowed: Vec<KeySource>,
+}
+
+/// a source setting a key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySource {
+    /// the name of the source, e.g. the path of the file.
+    pub origin: String,
+    pub layer: Layer,
+    /// the line of the key in the file, when it can be found.
+    pub line: Option<usize>,
+    /// the value the source sets, secrets masked.
+    pub value: Value,
+    /// whether the layer of the source is disabled.
+    pub disabled: bool,
+}
+
+/// this function will return where the value of a key came from and the values of the other sources it shadows.
+/// for a section the source is the one with the highest precedence, the section may be merged from several sources.
+/// # Example
+/// ```
+/// confmap::read_config();
+/// if let Some(explanation) = confmap::explain("server.port") {
+///     println!("{:?} from {:?}", explanation.value, explanation.source.map(|source| source.origin));
+/// }
+/// ```
+pub fn explain(key: &str) -> Option<Explanation> {
+    explain_in(&Loaded::current(), key)
+}
+
+fn explain_in(loaded: &Loaded, key: &str) -> Option<Explanation> {
+    let sources = loaded.values_of(key);
+    if sources.is_empty() {
+        return None;
+    }
+    let (values, origins) = loaded.resolve();
+    let path = split_path(key);
+    let value = lookup_path(&redact_map(&values), &path).cloned();
+    let winner = match (&value, origins.get(key)) {
+        (None, _) => None,
+        (Some(_), Some(origin)) => sources.iter().position(|source| !source.disabled && &source.origin == origin),
+        (Some(_), None) => sources.iter().position(|source| !source.disabled),
+    };
+    let mut sources: Vec<KeySource> = sources.into_iter().map(|source| key_source(source, &path)).collect();
+    let source = winner.map(|winner| sources.remove(winner));
+    Some(Explanation { key: key.to_string(), value, source, shadowed: sources })
+}
+
+fn key_source(source: LayerValue, path: &[String]) -> KeySource {
+    let line = match source.layer {
+        Layer::File => fs::read_to_string(Path::new(&source.origin)).ok().and_then(|text| line_of(&text, path)),
+        _ => None,
+    };
+    let masked = redact_map(&nested(path, source.value));
+    let value = lookup_path(&masked, path).cloned().unwrap_or(Value::Null);
+    KeySource { origin: source.origin, layer: source.layer, line, value, disabled: source.disabled }
+}
+
+/// a map holding `value` at `path`, to mask it like the whole configuration.
+fn nested(path: &[String], value: Value) -> Map<String, Value> {
+    let mut value = value;
+    for segment in path.iter().skip(1).rev() {
+        let mut map = Map::new();
+        map.insert(segment.clone(), value);
+        value = Value::Object(map);
+    }
+    let mut map = Map::new();
+    if let Some(first) = path.first() {
+        map.insert(first.clone(), value);
+    }
+    map
+}
+
+/// the 1-based line of a dotted key in a json or toml document, found by looking for its segments one after the other.
+fn line_of(text: &str, path: &[String]) -> Option<usize> {
+    let mut position = 0;
+    for segment in path.iter().filter(|segment| segment.parse::<usize>().is_err()) {
+        position = find_key(text, segment, position)?;
+    }
+    Some(text[..position].matches('\n').count() + 1)
+}
+
+/// the position of the next occurrence of `key` used as a key: quoted or bare, followed by `:`, `=`, `.` or `]`.
+fn find_key(text: &str, key: &str, from: usize) -> Option<usize> {
+    let mut start = from;
+    while let Some(found) = text[start..].find(key) {
+        let at = start + found;
+        let before = text[..at].chars().next_back();
+        let after = text[at + key.len()..].trim_start_matches(['"', '\'']).trim_start();
+        let is_key_start = before.is_none_or(|c| c.is_whitespace() || "\"'.[{,".contains(c));
+        if is_key_start && after.starts_with([':', '=', '.', ']']) {
+            return Some(at);
+        }
+        start = at + key.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MergeStrategy;
+
+    #[test]
+    fn the_winner_and_the_shadowed_values_are_listed() {
+        let mut loaded = Loaded::default();
+        loaded.push(Layer::Env, "env://APP", serde_json::from_str(r#"{"server": {"port": 9090}}"#).unwrap(), MergeStrategy::Replace);
+        loaded.push(Layer::File, "config.json", serde_json::from_str(r#"{"server": {"port": 8080}}"#).unwrap(), MergeStrategy::Replace);
+        let explanation = explain_in(&loaded, "server.port").unwrap();
+        assert_eq!(Some(Value::from(9090)), explanation.value);
+        assert_eq!("env://APP", explanation.source.unwrap().origin);
+        assert_eq!(vec![Value::from(8080)], explanation.shadowed.into_iter().map(|source| source.value).collect::<Vec<_>>());
+        assert!(explain_in(&loaded, "server.host").is_none());
+
+        let text = "{\n  \"port\": 1,\n  \"server\": {\n    \"host\": \"h\",\n    \"port\": 2\n  }\n}";
+        assert_eq!(Some(5), line_of(text, &split_path("server.port")));
+        assert_eq!(Some(2), line_of("[server]\nport = 2\n", &split_path("server.port")));
+    }
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::SourceError;
+
+/// the file formats a config file can be written in.
+/// json is always available, toml needs the `toml` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl Format {
+    /// guesses the format from the file extension, unknown extensions are read as json.
+    /// # Example
+    /// ```
+    /// use confmap::Format;
+    ///
+    /// assert_eq!(Format::Json, Format::from_path("config.json".as_ref()));
+    /// ```
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "toml")]
+            Some("toml") => Format::Toml,
+            _ => Format::Json,
+        }
+    }
+
+    /// the file extensions this format is recognised by.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Format::Json => &["json"],
+            #[cfg(feature = "toml")]
+            Format::Toml => &["toml"],
+        }
+    }
+
+    /// all formats compiled into this build.
+    pub fn all() -> &'static [Format] {
+        &[
+            Format::Json,
+            #[cfg(feature = "toml")]
+            Format::Toml,
+        ]
+    }
+
+    /// parses a document into a map of top-level keys.
+    pub fn parse(&self, content: &str) -> Result<Map<String, Value>, SourceError> {
+        match self {
+            Format::Json => Ok(serde_json::from_str(content)?),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::from_str(content).map_err(|e| SourceError::Parse(e.to_string())),
+        }
+    }
+
+    /// serializes a map into a document of this format.
+    pub fn serialize(&self, map: &Map<String, Value>) -> Result<String, Box<dyn Error>> {
+        match self {
+            Format::Json => Ok(serde_json::to_string_pretty(map)?),
+            #[cfg(feature = "toml")]
+            Format::Toml => Ok(toml::to_string_pretty(map)?),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static FROZEN: AtomicBool = AtomicBool::new(false);
+
+/// the error returned when changing the configuration after freeze.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrozenError;
+
+impl fmt::Display for FrozenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the configuration is frozen")
+    }
+}
+
+impl Error for FrozenError {}
+
+/// Make the configuration immutable for the rest of the process, typically once the startup is done.
+/// set, merge, try_read_config, reload, reset, disable_layer, enable_layer, set_precedence and the other functions changing it
+/// return a FrozenError afterwards, read_config and the reloads of the watched sources leave the configuration as it is.
+/// # Example
+/// ```
+/// confmap::read_config();
+/// confmap::freeze();
+/// assert!(confmap::is_frozen());
+/// ```
+pub fn freeze() {
+    FROZEN.store(true, Ordering::SeqCst);
+}
+
+/// this function will return whether freeze was called.
+pub fn is_frozen() -> bool {
+    FROZEN.load(Ordering::SeqCst)
+}
+
+/// fails once the configuration is frozen.
+pub(crate) fn check() -> Result<(), FrozenError> {
+    if is_frozen() {
+        Err(FrozenError)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn thaw() {
+    FROZEN.store(false, Ordering::SeqCst);
+}
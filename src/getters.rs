@@ -0,0 +1,676 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::num::{NonZeroU32, NonZeroU64};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::bytes::decode_bytes;
+use crate::coerce::MixedArrays;
+use crate::enums::variants_of;
+use crate::keys::KeyMode;
+use crate::size::parse_byte_size;
+use crate::{Config, ConfigSection, Rate};
+
+/// the error returned by the try_get getters, such as try_get_int64, and by get_as, get_parsed and get_enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// the key is not set.
+    KeyMissing(String),
+    /// the value at the key is of another type, `found` is its json type, e.g. "string".
+    WrongType { key: String, expected: &'static str, found: &'static str },
+    /// the value at the key is a number which does not fit in the requested type.
+    OutOfRange { key: String, expected: &'static str, value: String },
+    /// the string at the key does not parse, e.g. an address, `message` tells why.
+    Malformed { key: String, value: String, message: String },
+    /// the value at the key does not deserialize into the type requested from get_as, `message` is the error of serde.
+    Deserialize { key: String, message: String },
+}
+
+impl ConfigError {
+    /// the key the error is about.
+    pub fn key(&self) -> &str {
+        match self {
+            ConfigError::KeyMissing(key)
+            | ConfigError::WrongType { key, .. }
+            | ConfigError::OutOfRange { key, .. }
+            | ConfigError::Malformed { key, .. }
+            | ConfigError::Deserialize { key, .. } => key,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::KeyMissing(key) => write!(f, "{} is not set", key),
+            ConfigError::WrongType { key, expected, found } => write!(f, "{} is a {}, expected a {}", key, found, expected),
+            ConfigError::OutOfRange { key, expected, value } => write!(f, "{} is {}, out of the range of {}", key, value, expected),
+            ConfigError::Malformed { key, value, message } => write!(f, "{} is {:?}, {}", key, value, message),
+            ConfigError::Deserialize { key, message } => write!(f, "invalid value at {}: {}", key, message),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// how a view of the configuration converts its values and normalizes its keys, the same for the views taken from it such as get_map and sub.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// whether strings, numbers and bools are converted into each other, see set_coercion.
+    pub(crate) coercion: bool,
+    pub(crate) mixed_arrays: MixedArrays,
+    /// how the keys given to the getters of a section are normalized.
+    pub(crate) keys: KeyMode,
+}
+
+/// typed getters shared by every view of the configuration.
+/// an implementor only has to provide `get`, all other getters are derived from it.
+/// the array getters leave out the elements of another type, unless set_mixed_arrays says otherwise,
+/// or ConfigBuilder::mixed_arrays for a Config.
+/// a key can be a dotted path to a nested value, e.g. `database.primary.host`, with array indices such as `servers[0].host`.
+/// # Example
+/// ```
+/// use confmap::ConfigRead;
+///
+/// let serve = confmap::scope_for_subcommand("serve");
+/// serve.get_int64("port");
+/// ```
+pub trait ConfigRead {
+    /// this function will return Option<serde_json::Value> when you put a key argument.
+    fn get(&self, key: &str) -> Option<Value>;
+
+    /// the conversions of the typed getters, strict unless the view says otherwise.
+    #[doc(hidden)]
+    fn read_options(&self) -> ReadOptions {
+        ReadOptions::default()
+    }
+
+    /// this function will return the value of the first key of `keys` which is present, e.g. a new key then the old names
+    /// older deployments still use.
+    fn get_first(&self, keys: &[&str]) -> Option<Value> {
+        keys.iter().find_map(|key| self.get(key))
+    }
+
+    /// this function will return whether the key is present, even when its value is null, false or empty.
+    /// unlike `get_bool(key).is_some()` it does not depend on the type of the value.
+    fn is_set(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// this function will return None when the key is missing, Some(None) when it is set to null
+    /// and Some(Some(value)) otherwise, so an explicit null can mean something else than a key not configured.
+    fn get_optional(&self, key: &str) -> Option<Option<Value>> {
+        self.get(key).map(|value| Some(value).filter(|value| !value.is_null()))
+    }
+
+    /// this function will return the value at a key deserialized into any serde type, a struct, an enum, a HashMap, a Vec of structs...
+    /// a missing key gives ConfigError::KeyMissing, unless the type accepts null such as an Option.
+    fn get_as<T: DeserializeOwned>(&self, key: &str) -> Result<T, ConfigError>
+    where
+        Self: Sized,
+    {
+        let value = match self.get(key) {
+            Some(value) => value,
+            None => return serde_json::from_value(Value::Null).map_err(|_| ConfigError::KeyMissing(key.to_string())),
+        };
+        serde_json::from_value(value).map_err(|e| deserialize_error(key, e))
+    }
+
+    /// this function will return the string value at a key parsed with `T::from_str`, e.g. a version or an enum implementing FromStr.
+    /// a number or a bool is parsed from its text.
+    fn get_parsed<T>(&self, key: &str) -> Result<T, ConfigError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        Self: Sized,
+    {
+        let text = match self.get(key) {
+            None | Some(Value::Null) => return Err(ConfigError::KeyMissing(key.to_string())),
+            Some(Value::String(s)) => s,
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => value.to_string(),
+            Some(value) => return Err(ConfigError::WrongType { key: key.to_string(), expected: "string", found: type_name(&value) }),
+        };
+        text.parse().map_err(|e: T::Err| ConfigError::Malformed { key: key.to_string(), value: text.clone(), message: e.to_string() })
+    }
+
+    /// this function will return the string value at a key as a variant of an enum deriving Deserialize, whatever its case,
+    /// so `"Fast"`, `"fast"` and `"FAST"` all give the `fast` variant. the error of an unknown value lists the allowed ones.
+    fn get_enum<E: DeserializeOwned>(&self, key: &str) -> Result<E, ConfigError>
+    where
+        Self: Sized,
+    {
+        let Some(variants) = variants_of::<E>() else {
+            return self.get_as(key);
+        };
+        let s = match self.get(key) {
+            None | Some(Value::Null) => return Err(ConfigError::KeyMissing(key.to_string())),
+            Some(Value::String(s)) => s,
+            Some(value) => return Err(ConfigError::WrongType { key: key.to_string(), expected: "string", found: type_name(&value) }),
+        };
+        match variants.iter().find(|variant| variant.eq_ignore_ascii_case(s.trim())) {
+            Some(variant) => serde_json::from_value(Value::from(*variant)).map_err(|e| deserialize_error(key, e)),
+            None => {
+                let message = format!("not one of {}", variants.join(", "));
+                Err(ConfigError::Malformed { key: key.to_string(), value: s, message })
+            }
+        }
+    }
+
+    /// this function will return the value at a key deserialized like get_as, or the value of `default` when it is missing or invalid.
+    fn get_or_else<T: DeserializeOwned>(&self, key: &str, default: impl FnOnce() -> T) -> T
+    where
+        Self: Sized,
+    {
+        self.get_as(key).unwrap_or_else(|_| default())
+    }
+
+    /// this function will return the string at a key, or `default`.
+    fn get_string_or(&self, key: &str, default: &str) -> String {
+        self.get_string(key).unwrap_or_else(|| default.to_string())
+    }
+
+    /// this function will return the integer at a key, or `default`.
+    fn get_int64_or(&self, key: &str, default: i64) -> i64 {
+        self.get_int64(key).unwrap_or(default)
+    }
+
+    /// this function will return the number at a key, or `default`.
+    fn get_float64_or(&self, key: &str, default: f64) -> f64 {
+        self.get_float64(key).unwrap_or(default)
+    }
+
+    /// this function will return the bool at a key, or `default`.
+    fn get_bool_or(&self, key: &str, default: bool) -> bool {
+        self.get_bool(key).unwrap_or(default)
+    }
+
+    /// this function will return the string at a key, or why it cannot.
+    fn try_get_string(&self, key: &str) -> Result<String, ConfigError> {
+        try_convert(key, self.get(key), "string", |value| self.read_options().to_string(value))
+    }
+
+    /// this function will return the integer at a key, or why it cannot.
+    fn try_get_int64(&self, key: &str) -> Result<i64, ConfigError> {
+        let value = self.get(key);
+        if let Some(Value::Number(n)) = &value {
+            if n.as_i64().is_none() && (n.is_u64() || n.is_i64()) {
+                return Err(out_of_range(key, "i64", n));
+            }
+        }
+        try_convert(key, value, "integer", |value| self.read_options().to_i64(value))
+    }
+
+    /// this function will return the unsigned integer at a key, or why it cannot.
+    fn try_get_u64(&self, key: &str) -> Result<u64, ConfigError> {
+        let value = self.get(key);
+        if let Some(Value::Number(n)) = &value {
+            if n.is_i64() && n.as_u64().is_none() {
+                return Err(out_of_range(key, "u64", n));
+            }
+        }
+        try_convert(key, value, "integer", |value| self.read_options().to_u64(value))
+    }
+
+    /// this function will return the i32 at a key, or why it cannot.
+    fn try_get_i32(&self, key: &str) -> Result<i32, ConfigError> {
+        let n = self.try_get_int64(key)?;
+        i32::try_from(n).map_err(|_| out_of_range(key, "i32", n))
+    }
+
+    /// this function will return the i16 at a key, or why it cannot.
+    fn try_get_i16(&self, key: &str) -> Result<i16, ConfigError> {
+        let n = self.try_get_int64(key)?;
+        i16::try_from(n).map_err(|_| out_of_range(key, "i16", n))
+    }
+
+    /// this function will return the i8 at a key, or why it cannot.
+    fn try_get_i8(&self, key: &str) -> Result<i8, ConfigError> {
+        let n = self.try_get_int64(key)?;
+        i8::try_from(n).map_err(|_| out_of_range(key, "i8", n))
+    }
+
+    /// this function will return the u32 at a key, or why it cannot.
+    fn try_get_u32(&self, key: &str) -> Result<u32, ConfigError> {
+        let n = self.try_get_u64(key)?;
+        u32::try_from(n).map_err(|_| out_of_range(key, "u32", n))
+    }
+
+    /// this function will return the u16 at a key, or why it cannot.
+    fn try_get_u16(&self, key: &str) -> Result<u16, ConfigError> {
+        let n = self.try_get_u64(key)?;
+        u16::try_from(n).map_err(|_| out_of_range(key, "u16", n))
+    }
+
+    /// this function will return the u8 at a key, or why it cannot.
+    fn try_get_u8(&self, key: &str) -> Result<u8, ConfigError> {
+        let n = self.try_get_u64(key)?;
+        u8::try_from(n).map_err(|_| out_of_range(key, "u8", n))
+    }
+
+    /// this function will return the usize at a key, or why it cannot.
+    fn try_get_usize(&self, key: &str) -> Result<usize, ConfigError> {
+        let n = self.try_get_u64(key)?;
+        usize::try_from(n).map_err(|_| out_of_range(key, "usize", n))
+    }
+
+    /// this function will return the number at a key, or why it cannot.
+    fn try_get_float64(&self, key: &str) -> Result<f64, ConfigError> {
+        try_convert(key, self.get(key), "number", |value| self.read_options().to_f64(value))
+    }
+
+    /// this function will return the bool at a key, or why it cannot.
+    fn try_get_bool(&self, key: &str) -> Result<bool, ConfigError> {
+        try_convert(key, self.get(key), "bool", |value| self.read_options().to_bool(value))
+    }
+
+    /// this function will return Option<String> when you put a key argument.
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.get(key).and_then(|value| self.read_options().to_string(&value))
+    }
+
+    /// this function will return Option<char> when you put a key argument, the value is a string of a single character.
+    fn get_char(&self, key: &str) -> Option<char> {
+        let s = self.get_string(key)?;
+        let mut chars = s.chars();
+        chars.next().filter(|_| chars.next().is_none())
+    }
+
+    /// this function will return Option<Vec<String>> when you put a key argument.
+    fn get_string_array(&self, key: &str) -> Option<Vec<String>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_string(element)))
+    }
+
+    /// this function will return Option<i64> when you put a key argument.
+    fn get_int64(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(|value| self.read_options().to_i64(&value))
+    }
+
+    /// this function will return Option<Vec<i64>> when you put a key argument.
+    fn get_int64_array(&self, key: &str) -> Option<Vec<i64>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_i64(element)))
+    }
+
+    /// this function will return Option<Vec<i32>> when you put a key argument.
+    /// a number beyond the range of i32 is an element of another type.
+    fn get_i32_array(&self, key: &str) -> Option<Vec<i32>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_i64(element).and_then(|n| i32::try_from(n).ok())))
+    }
+
+    /// this function will return Option<Vec<u64>> when you put a key argument.
+    fn get_u64_array(&self, key: &str) -> Option<Vec<u64>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_u64(element)))
+    }
+
+    /// this function will return Option<i64> when you put a key argument, the same as get_int64.
+    fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get_int64(key)
+    }
+
+    /// this function will return Option<i32> when you put a key argument.
+    /// a number beyond the range of i32 gives None rather than a truncated value.
+    fn get_i32(&self, key: &str) -> Option<i32> {
+        self.get_int64(key).and_then(|n| i32::try_from(n).ok())
+    }
+
+    /// this function will return Option<i16> when you put a key argument.
+    /// a number beyond the range of i16 gives None rather than a truncated value.
+    fn get_i16(&self, key: &str) -> Option<i16> {
+        self.get_int64(key).and_then(|n| i16::try_from(n).ok())
+    }
+
+    /// this function will return Option<i8> when you put a key argument.
+    /// a number beyond the range of i8 gives None rather than a truncated value.
+    fn get_i8(&self, key: &str) -> Option<i8> {
+        self.get_int64(key).and_then(|n| i8::try_from(n).ok())
+    }
+
+    /// this function will return Option<i8> when you put a key argument.
+    #[deprecated(note = "use get_i8, named like the other sized getters")]
+    fn get_int8(&self, key: &str) -> Option<i8> {
+        self.get_i8(key)
+    }
+
+    /// this function will return Option<u64> when you put a key argument.
+    /// a negative number gives None.
+    fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(|value| self.read_options().to_u64(&value))
+    }
+
+    /// this function will return Option<u32> when you put a key argument.
+    /// a negative number or a number beyond the range of u32 gives None rather than a truncated value.
+    fn get_u32(&self, key: &str) -> Option<u32> {
+        self.get_u64(key).and_then(|n| u32::try_from(n).ok())
+    }
+
+    /// this function will return Option<u16> when you put a key argument.
+    /// a negative number or a number beyond the range of u16 gives None rather than a truncated value.
+    fn get_u16(&self, key: &str) -> Option<u16> {
+        self.get_u64(key).and_then(|n| u16::try_from(n).ok())
+    }
+
+    /// this function will return Option<u8> when you put a key argument.
+    /// a negative number or a number beyond the range of u8 gives None rather than a truncated value.
+    fn get_u8(&self, key: &str) -> Option<u8> {
+        self.get_u64(key).and_then(|n| u8::try_from(n).ok())
+    }
+
+    /// this function will return Option<usize> when you put a key argument.
+    /// a negative number or a number beyond the range of usize gives None rather than a truncated value.
+    fn get_usize(&self, key: &str) -> Option<usize> {
+        self.get_u64(key).and_then(|n| usize::try_from(n).ok())
+    }
+
+    /// this function will return Option<NonZeroU32> when you put a key argument, e.g. a count of workers.
+    /// zero gives None, like a negative number or a number beyond the range of u32.
+    fn get_nonzero_u32(&self, key: &str) -> Option<NonZeroU32> {
+        self.get_u32(key).and_then(NonZeroU32::new)
+    }
+
+    /// this function will return Option<NonZeroU64> when you put a key argument.
+    /// zero gives None, like a negative number.
+    fn get_nonzero_u64(&self, key: &str) -> Option<NonZeroU64> {
+        self.get_u64(key).and_then(NonZeroU64::new)
+    }
+
+    /// this function will return the two numbers of a value such as `"8000-9000"` or `[8000, 9000]` when you put a key argument.
+    fn get_u64_pair(&self, key: &str) -> Option<(u64, u64)> {
+        match self.get(key)? {
+            Value::String(s) => {
+                let (first, second) = s.split_once('-')?;
+                Some((first.trim().parse().ok()?, second.trim().parse().ok()?))
+            }
+            Value::Array(arr) => match arr.as_slice() {
+                [first, second] => {
+                    let options = self.read_options();
+                    Some((options.to_u64(first)?, options.to_u64(second)?))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// this function will return the range of a value such as `"8000-9000"` or `[8000, 9000]` when you put a key argument,
+    /// e.g. a range of ports. both bounds are included, a start after the end gives None.
+    fn get_range(&self, key: &str) -> Option<RangeInclusive<u64>> {
+        self.get_u64_pair(key).filter(|(start, end)| start <= end).map(|(start, end)| start..=end)
+    }
+
+    /// this function will return Option<i128> when you put a key argument.
+    /// a number beyond the 64 bits range needs the `arbitrary_precision` feature, or can be written as a string.
+    fn get_i128(&self, key: &str) -> Option<i128> {
+        match self.get(key) {
+            Some(Value::Number(n)) => n.as_i128(),
+            Some(Value::String(s)) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// this function will return Option<u128> when you put a key argument.
+    /// a number beyond the 64 bits range needs the `arbitrary_precision` feature, or can be written as a string.
+    fn get_u128(&self, key: &str) -> Option<u128> {
+        match self.get(key) {
+            Some(Value::Number(n)) => n.as_u128(),
+            Some(Value::String(s)) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// this function will return Option<rust_decimal::Decimal> when you put a key argument.
+    /// the number is read from its exact text in the file, so `0.1` is exactly 0.1, it can also be written as a string.
+    #[cfg(feature = "rust_decimal")]
+    fn get_decimal(&self, key: &str) -> Option<rust_decimal::Decimal> {
+        use std::str::FromStr;
+
+        let text = match self.get(key) {
+            Some(Value::Number(n)) => n.to_string(),
+            Some(Value::String(s)) => s.trim().to_string(),
+            _ => return None,
+        };
+        rust_decimal::Decimal::from_str(&text)
+            .or_else(|_| rust_decimal::Decimal::from_scientific(&text))
+            .ok()
+    }
+
+    /// this function will return Option<chrono::DateTime<Utc>> when you put a key argument.
+    /// the value is an RFC 3339 string such as `"2025-03-01T12:00:00Z"`, or a number of seconds since the unix epoch.
+    #[cfg(feature = "chrono")]
+    fn get_datetime(&self, key: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self.get(key) {
+            Some(Value::String(s)) => chrono::DateTime::parse_from_rfc3339(s.trim()).ok().map(|datetime| datetime.to_utc()),
+            Some(Value::Number(n)) => match n.as_i64() {
+                Some(seconds) => chrono::DateTime::from_timestamp(seconds, 0),
+                None => {
+                    let seconds = n.as_f64().filter(|seconds| seconds.is_finite())?;
+                    let nanos = (seconds.rem_euclid(1.0) * 1e9) as u32;
+                    chrono::DateTime::from_timestamp(seconds.floor() as i64, nanos)
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// this function will return Option<uuid::Uuid> when you put a key argument, the value is a string.
+    #[cfg(feature = "uuid")]
+    fn get_uuid(&self, key: &str) -> Option<uuid::Uuid> {
+        self.get(key).and_then(|value| value.as_str().and_then(|s| uuid::Uuid::parse_str(s.trim()).ok()))
+    }
+
+    /// this function will return Option<Vec<uuid::Uuid>> when you put a key argument.
+    #[cfg(feature = "uuid")]
+    fn get_uuid_array(&self, key: &str) -> Option<Vec<uuid::Uuid>> {
+        self.get(key).and_then(|value| self.read_options().to_array(value, |element| element.as_str().and_then(|s| uuid::Uuid::parse_str(s.trim()).ok())))
+    }
+
+    /// this function will return Option<log::LevelFilter> when you put a key argument.
+    /// the value is a level such as `"debug"`, `"INFO"` or `"off"`, whatever its case.
+    #[cfg(feature = "log")]
+    fn get_log_level(&self, key: &str) -> Option<log::LevelFilter> {
+        self.get(key).and_then(|value| value.as_str().and_then(|s| s.trim().parse().ok()))
+    }
+
+    /// this function will return Option<f64> when you put a key argument.
+    fn get_float64(&self, key: &str) -> Option<f64> {
+        self.get(key).and_then(|value| self.read_options().to_f64(&value))
+    }
+
+    /// this function will return Option<Vec<f64>> when you put a key argument.
+    fn get_float64_array(&self, key: &str) -> Option<Vec<f64>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_f64(element)))
+    }
+
+    /// this function will return Option<Vec<f32>> when you put a key argument.
+    fn get_f32_array(&self, key: &str) -> Option<Vec<f32>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_f64(element).map(|n| n as f32)))
+    }
+
+    /// this function will return Option<f32> when you put a key argument.
+    fn get_float32(&self, key: &str) -> Option<f32> {
+        self.get_float64(key).map(|n| n as f32)
+    }
+
+    /// this function will return Option<bool> when you put a key argument.
+    fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key).and_then(|value| self.read_options().to_bool(&value))
+    }
+
+    /// this function will return Option<Vec<bool>> when you put a key argument.
+    fn get_bool_array(&self, key: &str) -> Option<Vec<bool>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_array(value, |element| options.to_bool(element)))
+    }
+
+    /// this function will return Option<Vec<serde_json::Value>> when you put a key argument.
+    /// only the object elements of the array are returned, the others are left out or reject the array as set by set_mixed_arrays.
+    /// get_value_array returns every element.
+    fn get_array(&self, key: &str) -> Option<Vec<Value>> {
+        self.get(key).and_then(|value| self.read_options().to_array(value, |element| Some(element.clone()).filter(Value::is_object)))
+    }
+
+    /// this function will return Option<Vec<serde_json::Value>> when you put a key argument, every element of the array,
+    /// strings, numbers, bools, nested arrays and objects alike.
+    fn get_value_array(&self, key: &str) -> Option<Vec<Value>> {
+        match self.get(key) {
+            Some(Value::Array(arr)) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// this function will return Option<Vec<Map<String, Value>>> when you put a key argument, every object element of the array.
+    /// the other elements are left out or reject the array as set by set_mixed_arrays.
+    fn get_map_array(&self, key: &str) -> Option<Vec<Map<String, Value>>> {
+        self.get(key).and_then(|value| self.read_options().to_array(value, |element| element.as_object().cloned()))
+    }
+
+    /// this function will return Option<ConfigSection> when you put a key argument.
+    /// the section has the same typed getters, which read the keys relative to it.
+    fn get_map(&self, key: &str) -> Option<ConfigSection> {
+        match self.get(key) {
+            Some(Value::Object(map)) => Some(ConfigSection::with_options(map, self.read_options())),
+            _ => None,
+        }
+    }
+
+    /// this function will return Option<HashMap<String, String>> when you put a key argument, e.g. labels or headers.
+    /// the values which are not strings are left out, like the elements of the array getters.
+    fn get_string_map(&self, key: &str) -> Option<HashMap<String, String>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_map(value, |element| options.to_string(element)))
+    }
+
+    /// this function will return Option<HashMap<String, i64>> when you put a key argument.
+    /// the values which are not integers are left out, like the elements of the array getters.
+    fn get_int64_map(&self, key: &str) -> Option<HashMap<String, i64>> {
+        let options = self.read_options();
+        self.get(key).and_then(|value| options.to_map(value, |element| options.to_i64(element)))
+    }
+
+    /// this function will return the object at a key as a Config, like viper's Sub,
+    /// so a component can be given only its own section and read its keys relative to it.
+    fn sub(&self, key: &str) -> Option<Config> {
+        self.get_map(key).map(|section| Config::with_options(section.into_map(), self.read_options()))
+    }
+
+    /// this function will return the number of bytes of a size, such as `"10MB"`, `"512KiB"` or `"1.5G"`, when you put a key argument.
+    /// `KB`, `MB`, `GB`... are powers of 1000, `KiB`, `MiB`, `GiB`... powers of 1024, a number is a count of bytes.
+    fn get_byte_size(&self, key: &str) -> Option<u64> {
+        match self.get(key) {
+            Some(Value::Number(n)) => n.as_u64(),
+            Some(Value::String(s)) => parse_byte_size(&s),
+            _ => None,
+        }
+    }
+
+    /// this function will return Option<Vec<u8>> when you put a key argument, e.g. an HMAC secret.
+    /// the value is a base64 string, or a hex string starting with `hex:` or `0x`.
+    fn get_bytes(&self, key: &str) -> Option<Vec<u8>> {
+        self.get(key).and_then(|value| value.as_str().and_then(decode_bytes))
+    }
+
+    /// this function will return Option<SocketAddr> when you put a key argument, the value is a string such as `"0.0.0.0:8080"` or `"[::1]:443"`.
+    /// a malformed address gives None, try_get_socket_addr tells why.
+    fn get_socket_addr(&self, key: &str) -> Option<SocketAddr> {
+        self.try_get_socket_addr(key).ok()
+    }
+
+    /// this function will return the socket address at a key, or why it cannot.
+    fn try_get_socket_addr(&self, key: &str) -> Result<SocketAddr, ConfigError> {
+        parse_addr(key, &self.get(key).ok_or_else(|| ConfigError::KeyMissing(key.to_string()))?, "socket address")
+    }
+
+    /// this function will return Option<Vec<SocketAddr>> when you put a key argument, e.g. the addresses to listen on.
+    /// unlike the other array getters a malformed element gives None for the whole array.
+    fn get_socket_addr_array(&self, key: &str) -> Option<Vec<SocketAddr>> {
+        self.try_get_socket_addr_array(key).ok()
+    }
+
+    /// this function will return the socket addresses at a key, or why it cannot, naming the first malformed element.
+    fn try_get_socket_addr_array(&self, key: &str) -> Result<Vec<SocketAddr>, ConfigError> {
+        parse_addr_array(key, self.get(key), "socket address")
+    }
+
+    /// this function will return Option<IpAddr> when you put a key argument, the value is a string such as `"10.0.0.1"` or `"::1"`.
+    /// a malformed address gives None, try_get_ip_addr tells why.
+    fn get_ip_addr(&self, key: &str) -> Option<IpAddr> {
+        self.try_get_ip_addr(key).ok()
+    }
+
+    /// this function will return the ip address at a key, or why it cannot.
+    fn try_get_ip_addr(&self, key: &str) -> Result<IpAddr, ConfigError> {
+        parse_addr(key, &self.get(key).ok_or_else(|| ConfigError::KeyMissing(key.to_string()))?, "ip address")
+    }
+
+    /// this function will return Option<Vec<IpAddr>> when you put a key argument.
+    /// unlike the other array getters a malformed element gives None for the whole array.
+    fn get_ip_addr_array(&self, key: &str) -> Option<Vec<IpAddr>> {
+        self.try_get_ip_addr_array(key).ok()
+    }
+
+    /// this function will return the ip addresses at a key, or why it cannot, naming the first malformed element.
+    fn try_get_ip_addr_array(&self, key: &str) -> Result<Vec<IpAddr>, ConfigError> {
+        parse_addr_array(key, self.get(key), "ip address")
+    }
+
+    /// this function will return Option<Rate> when you put a key argument.
+    /// the value is a string such as `"100/s"`, `"5000/min"` or `"20/10s"`.
+    fn get_rate(&self, key: &str) -> Option<Rate> {
+        self.get(key).and_then(|value| value.as_str().and_then(|s| s.parse().ok()))
+    }
+}
+
+/// parses an address, a string which does not parse is malformed.
+fn parse_addr<T>(key: &str, value: &Value, expected: &'static str) -> Result<T, ConfigError>
+where
+    T: FromStr<Err = AddrParseError>,
+{
+    let s = value.as_str().ok_or_else(|| ConfigError::WrongType { key: key.to_string(), expected, found: type_name(value) })?;
+    s.trim().parse().map_err(|e: AddrParseError| ConfigError::Malformed { key: key.to_string(), value: s.to_string(), message: e.to_string() })
+}
+
+fn parse_addr_array<T>(key: &str, value: Option<Value>, expected: &'static str) -> Result<Vec<T>, ConfigError>
+where
+    T: FromStr<Err = AddrParseError>,
+{
+    match value.ok_or_else(|| ConfigError::KeyMissing(key.to_string()))? {
+        Value::Array(arr) => arr.iter().enumerate().map(|(index, element)| parse_addr(&format!("{}[{}]", key, index), element, expected)).collect(),
+        value => Err(ConfigError::WrongType { key: key.to_string(), expected: "array", found: type_name(&value) }),
+    }
+}
+
+/// converts the value at a key, a value which `convert` rejects is of the wrong type.
+fn try_convert<T>(key: &str, value: Option<Value>, expected: &'static str, convert: impl Fn(&Value) -> Option<T>) -> Result<T, ConfigError> {
+    let value = value.ok_or_else(|| ConfigError::KeyMissing(key.to_string()))?;
+    convert(&value).ok_or_else(|| ConfigError::WrongType { key: key.to_string(), expected, found: type_name(&value) })
+}
+
+fn deserialize_error(key: &str, e: serde_json::Error) -> ConfigError {
+    ConfigError::Deserialize { key: key.to_string(), message: e.to_string() }
+}
+
+fn out_of_range(key: &str, expected: &'static str, value: impl fmt::Display) -> ConfigError {
+    ConfigError::OutOfRange { key: key.to_string(), expected, value: value.to_string() }
+}
+
+/// the json type of a value.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
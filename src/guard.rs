@@ -0,0 +1,131 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::diff::{changed_keys, flatten};
+use crate::pattern::{covers, split_path};
+
+type Approval = Arc<dyn Fn(&ChangeReview) -> bool + Send + Sync>;
+
+static GUARD: Lazy<Mutex<Option<ChangeGuard>>> = Lazy::new(|| Mutex::new(None));
+
+/// what a guarded change would do, handed to the approval callback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeReview {
+    /// the dotted keys the change adds, removes or modifies.
+    pub changed: Vec<String>,
+    /// the changed keys matching a pinned pattern.
+    pub pinned: Vec<String>,
+    /// the share of the current keys the change touches, in percent.
+    pub changed_percent: f64,
+}
+
+/// protects a running process from a wrong configuration pushed by a watched (remote) source.
+/// a change touching more than `max_changed_percent` of the keys, or any pinned key, is only applied
+/// when the approval callback accepts it, without a callback it is rejected.
+/// # Example
+/// ```
+/// use confmap::ChangeGuard;
+///
+/// confmap::set_change_guard(
+///     ChangeGuard::new()
+///         .max_changed_percent(20.0)
+///         .pin("auth.*")
+///         .approve_with(|review| {
+///             println!("rejecting config push touching {:?}", review.changed);
+///             false
+///         }),
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct ChangeGuard {
+    max_changed_percent: Option<f64>,
+    pinned: Vec<Vec<String>>,
+    approve: Option<Approval>,
+}
+
+impl ChangeGuard {
+    /// a guard letting every change through until limits are set.
+    pub fn new() -> ChangeGuard {
+        ChangeGuard::default()
+    }
+
+    /// the largest share of the keys, in percent, a change may touch without approval.
+    pub fn max_changed_percent(mut self, percent: f64) -> ChangeGuard {
+        self.max_changed_percent = Some(percent);
+        self
+    }
+
+    /// keys matching this pattern (see mark_secret for the syntax) may not change without approval.
+    pub fn pin(mut self, pattern: &str) -> ChangeGuard {
+        self.pinned.push(split_path(pattern));
+        self
+    }
+
+    /// the callback deciding whether a change exceeding the limits is applied anyway.
+    pub fn approve_with<F>(mut self, approve: F) -> ChangeGuard
+    where
+        F: Fn(&ChangeReview) -> bool + Send + Sync + 'static,
+    {
+        self.approve = Some(Arc::new(approve));
+        self
+    }
+
+    fn allows(&self, current: &Map<String, Value>, next: &Map<String, Value>) -> bool {
+        let changed = changed_keys(current, next);
+        if changed.is_empty() {
+            return true;
+        }
+        let pinned: Vec<String> = changed
+            .iter()
+            .filter(|key| self.pinned.iter().any(|pattern| covers(pattern, &split_path(key))))
+            .cloned()
+            .collect();
+        let total = flatten(current).len().max(1);
+        let changed_percent = changed.len() as f64 * 100.0 / total as f64;
+        let too_many = self.max_changed_percent.is_some_and(|max| changed_percent > max);
+        if !too_many && pinned.is_empty() {
+            return true;
+        }
+        let review = ChangeReview { changed, pinned, changed_percent };
+        match &self.approve {
+            Some(approve) => approve(&review),
+            None => {
+                println!("config change rejected by the change guard: {:?}", review);
+                false
+            }
+        }
+    }
+}
+
+/// Set the guard reviewing the changes reported by watched sources.
+pub fn set_change_guard(guard: ChangeGuard) {
+    *GUARD.lock().unwrap() = Some(guard);
+}
+
+/// whether the change from `current` to `next` may be applied.
+pub(crate) fn review(current: &Map<String, Value>, next: &Map<String, Value>) -> bool {
+    let guard = GUARD.lock().unwrap().clone();
+    guard.is_none_or(|guard| guard.allows(current, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mass_changes_and_pinned_keys_need_approval() {
+        let current: Map<String, Value> = serde_json::from_str(r#"{"a": 1, "b": 2, "c": 3, "auth": {"key": "k"}}"#).unwrap();
+        let one_change: Map<String, Value> = serde_json::from_str(r#"{"a": 9, "b": 2, "c": 3, "auth": {"key": "k"}}"#).unwrap();
+        let mass_change: Map<String, Value> = serde_json::from_str(r#"{"a": 9, "b": 9, "c": 9, "auth": {"key": "k"}}"#).unwrap();
+        let pinned_change: Map<String, Value> = serde_json::from_str(r#"{"a": 1, "b": 2, "c": 3, "auth": {"key": "x"}}"#).unwrap();
+        let guard = ChangeGuard::new().max_changed_percent(50.0).pin("auth.*");
+        assert!(guard.allows(&current, &one_change));
+        assert!(!guard.allows(&current, &mass_change));
+        assert!(!guard.allows(&current, &pinned_change));
+        let guard = guard.approve_with(|review| review.pinned.is_empty());
+        assert!(guard.allows(&current, &mass_change));
+        assert!(!guard.allows(&current, &pinned_change));
+    }
+}
@@ -0,0 +1,156 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::merge::deep_merge;
+use crate::write::write_atomic;
+use crate::{config_name, config_path, FileSource, Format, Source};
+
+/// Import a configuration laid out for the `config` crate.
+/// `path` is either a single settings file, or a folder using the hierarchical layout of the `config` crate:
+/// `default.*`, then `$RUN_MODE.*` (development when unset), then `local.*`, each one overriding the previous.
+/// the merged result is written as the config file set by set_config_name and add_config_path,
+/// (config.json next to the executable when they are not set) and the written path is returned.
+/// an existing config file is never overwritten.
+/// # Example
+/// ```no_run
+/// confmap::set_config_name("config.json");
+/// confmap::import_from_config_rs("config/").expect("failed to import settings");
+/// confmap::read_config();
+/// ```
+pub fn import_from_config_rs(path: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let path = Path::new(path);
+    let mut configs = Map::new();
+    if path.is_dir() {
+        let run_mode = env::var("RUN_MODE").unwrap_or_else(|_| "development".to_string());
+        for stem in ["default", run_mode.as_str(), "local"] {
+            if let Some(file) = find_file_with_stem(path, stem) {
+                deep_merge(&mut configs, read_file(&file)?);
+            }
+        }
+    } else {
+        configs = read_file(path)?;
+    }
+    write_canonical(&configs)
+}
+
+/// Import a dotenv file.
+/// keys are lowercased and `__` separates nested sections, so `DATABASE__HOST=db` becomes `{"database": {"host": "db"}}`.
+/// unquoted booleans and numbers keep their type, everything else is a string.
+/// the result is written like import_from_config_rs does and the written path is returned.
+/// # Example
+/// ```no_run
+/// confmap::import_from_dotenv(".env").expect("failed to import .env");
+/// ```
+pub fn import_from_dotenv(path: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    write_canonical(&parse_dotenv(&content))
+}
+
+fn find_file_with_stem(dir: &Path, stem: &str) -> Option<PathBuf> {
+    Format::all()
+        .iter()
+        .flat_map(|format| format.extensions())
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .find(|file| file.is_file())
+}
+
+fn read_file(path: &Path) -> Result<Map<String, Value>, Box<dyn Error>> {
+    Ok(FileSource::new(path).load()?)
+}
+
+fn write_canonical(configs: &Map<String, Value>) -> Result<PathBuf, Box<dyn Error>> {
+    let name = config_name();
+    let name = if name.is_empty() { "config.json".to_string() } else { name };
+    let dir = if config_path().is_empty() {
+        let exe = env::current_exe()?;
+        exe.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        PathBuf::from(config_path())
+    };
+    let target = dir.join(name);
+    let document = Format::from_path(&target).serialize(configs)?;
+    fs::create_dir_all(&dir)?;
+    write_atomic(&target, document.as_bytes(), false).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", target.display())),
+        _ => e,
+    })?;
+    Ok(target)
+}
+
+fn parse_dotenv(content: &str) -> Map<String, Value> {
+    let mut configs = Map::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if let Some((key, raw)) = line.split_once('=') {
+            let raw = raw.trim();
+            let quoted = raw.len() >= 2
+                && ((raw.starts_with('"') && raw.ends_with('"')) || (raw.starts_with('\'') && raw.ends_with('\'')));
+            let value = if quoted {
+                Value::String(raw[1..raw.len() - 1].to_string())
+            } else {
+                let raw = raw.split(" #").next().unwrap_or_default().trim();
+                parse_scalar(raw)
+            };
+            let key = key.trim().to_lowercase();
+            insert_nested(&mut configs, &key.split("__").collect::<Vec<_>>(), value);
+        }
+    }
+    configs
+}
+
+/// reads an untyped string the way a config file would have typed it.
+pub(crate) fn parse_scalar(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        Value::Bool(b)
+    } else if let Ok(i) = raw.parse::<i64>() {
+        Value::from(i)
+    } else if let Ok(f) = raw.parse::<f64>() {
+        Value::from(f)
+    } else {
+        Value::String(raw.to_string())
+    }
+}
+
+pub(crate) fn insert_nested(map: &mut Map<String, Value>, keys: &[&str], value: Value) {
+    match keys {
+        [] => {}
+        [last] => {
+            map.insert(last.to_string(), value);
+        }
+        [first, rest @ ..] => {
+            let entry = map.entry(first.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            if let Value::Object(child) = entry {
+                insert_nested(child, rest, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dotenv_is_nested_and_typed() {
+        let configs = parse_dotenv(
+            "# comment\nexport DATABASE__HOST=db.local\nDATABASE__PORT=5432\nDEBUG=true\nNAME=\"my app\"\n",
+        );
+        let expected: Map<String, Value> = serde_json::from_str(
+            r#"{"database": {"host": "db.local", "port": 5432}, "debug": true, "name": "my app"}"#,
+        )
+        .unwrap();
+        assert_eq!(expected, configs);
+    }
+}
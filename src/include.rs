@@ -0,0 +1,147 @@
+//! the `$include` directive of config files.
+//! `{"$include": "shared/db.json"}` or `{"$include": ["base.json", "tls.json"]}` loads the listed files,
+//! relative to the including file, and deep-merges them in order, the including file's own keys are merged last.
+//! in toml the key has to be quoted: `"$include" = ["base.toml"]`.
+//! a name can be a glob such as `plugins/*.json` or `conf/**/*.toml`, its matches are merged in lexical order.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::merge::deep_merge;
+use crate::pattern::glob_match;
+use crate::{Format, SourceError};
+
+const INCLUDE: &str = "$include";
+
+/// reads a config file and the files it includes, `chain` holds the files being included to detect cycles.
+pub(crate) fn load_file(path: &Path, format: Format, chain: &mut Vec<PathBuf>) -> Result<Map<String, Value>, SourceError> {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if chain.contains(&canonical) {
+        let cycle: Vec<String> = chain.iter().chain([&canonical]).map(|file| file.display().to_string()).collect();
+        return Err(SourceError::Parse(format!("include cycle: {}", cycle.join(" -> "))));
+    }
+    println!("reading file {}", path.display());
+    let mut own = format.parse(&fs::read_to_string(path)?)?;
+    let includes = match own.remove(INCLUDE) {
+        None => return Ok(own),
+        Some(Value::String(file)) => vec![file],
+        Some(Value::Array(files)) => files
+            .into_iter()
+            .map(|file| match file {
+                Value::String(file) => Ok(file),
+                _ => Err(SourceError::Parse(format!("{}: {} must list file names", path.display(), INCLUDE))),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(SourceError::Parse(format!("{}: {} must be a file name or a list of them", path.display(), INCLUDE))),
+    };
+    chain.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut configs = Map::new();
+    for include in includes {
+        let include = dir.join(include);
+        let files = if is_glob(&include) { glob_files(&include) } else { vec![include] };
+        for file in files {
+            match load_file(&file, Format::from_path(&file), chain) {
+                Ok(included) => deep_merge(&mut configs, included),
+                Err(e) => {
+                    chain.pop();
+                    return Err(e);
+                }
+            }
+        }
+    }
+    chain.pop();
+    deep_merge(&mut configs, own);
+    Ok(configs)
+}
+
+/// whether a path has `*` or `?` in it.
+pub(crate) fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?'])
+}
+
+/// the files matching a path glob, sorted. `*` and `?` match inside one component, `**` any number of folders.
+/// hidden files and folders are only matched by a component starting with a dot.
+pub(crate) fn glob_files(pattern: &Path) -> Vec<PathBuf> {
+    let mut matches = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let component = component.as_os_str().to_string_lossy();
+        if !component.contains(['*', '?']) {
+            matches.iter_mut().for_each(|path| path.push(component.as_ref()));
+        } else if component == "**" {
+            matches = matches.into_iter().flat_map(with_sub_dirs).collect();
+        } else {
+            matches = matches
+                .into_iter()
+                .flat_map(|dir| children(&dir).into_iter().filter(|path| name_matches(&component, path)))
+                .collect();
+        }
+    }
+    matches.retain(|path| path.is_file());
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+fn children(dir: &Path) -> Vec<PathBuf> {
+    let read_from = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    match fs::read_dir(read_from) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| dir.join(entry.file_name()))).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn with_sub_dirs(dir: PathBuf) -> Vec<PathBuf> {
+    let mut dirs = vec![dir.clone()];
+    for child in children(&dir) {
+        if child.is_dir() && !name_of(&child).starts_with('.') {
+            dirs.extend(with_sub_dirs(child));
+        }
+    }
+    dirs
+}
+
+fn name_matches(pattern: &str, path: &Path) -> bool {
+    let name = name_of(path);
+    (pattern.starts_with('.') || !name.starts_with('.')) && glob_match(pattern, &name)
+}
+
+fn name_of(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_are_merged_and_cycles_rejected() {
+        let dir = std::env::temp_dir().join(format!("confmap_include_{}", std::process::id()));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        fs::write(dir.join("main.json"), r#"{"$include": ["shared/db.json"], "db": {"port": 2}}"#).unwrap();
+        fs::write(dir.join("shared/db.json"), r#"{"db": {"host": "h", "port": 1}}"#).unwrap();
+        let configs = load_file(&dir.join("main.json"), Format::Json, &mut Vec::new()).unwrap();
+        assert_eq!(serde_json::json!({"db": {"host": "h", "port": 2}}), Value::Object(configs));
+
+        fs::write(dir.join("shared/db.json"), r#"{"$include": "../main.json"}"#).unwrap();
+        let result = load_file(&dir.join("main.json"), Format::Json, &mut Vec::new());
+        assert!(matches!(result, Err(SourceError::Parse(message)) if message.starts_with("include cycle")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn glob_includes_are_merged_in_order() {
+        let dir = std::env::temp_dir().join(format!("confmap_glob_{}", std::process::id()));
+        fs::create_dir_all(dir.join("plugins/extra")).unwrap();
+        fs::write(dir.join("main.json"), r#"{"$include": "plugins/**/*.json"}"#).unwrap();
+        fs::write(dir.join("plugins/a.json"), r#"{"plugins": {"a": true}, "order": "a"}"#).unwrap();
+        fs::write(dir.join("plugins/b.json"), r#"{"plugins": {"b": true}, "order": "b"}"#).unwrap();
+        fs::write(dir.join("plugins/extra/c.json"), r#"{"plugins": {"c": true}}"#).unwrap();
+        fs::write(dir.join("plugins/notes.txt"), "").unwrap();
+        let configs = load_file(&dir.join("main.json"), Format::Json, &mut Vec::new()).unwrap();
+        assert_eq!(serde_json::json!({"plugins": {"a": true, "b": true, "c": true}, "order": "b"}), Value::Object(configs));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
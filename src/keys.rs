@@ -0,0 +1,313 @@
+//! the normalization of the keys, applied to the loaded configuration and to the keys given to the getters.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::deprecation::warn_deprecated;
+use crate::freeze::{check, FrozenError};
+use crate::pattern::{insert_path, lookup_path, remove_path, split_path};
+
+type KeyTransform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// the key settings as they were set, guarded while one of them changes.
+static SETTINGS: Lazy<Mutex<KeySettings>> = Lazy::new(|| Mutex::new(KeySettings::default()));
+/// the rules built from SETTINGS every time it changes, so a getter only has to load them.
+static RULES: Lazy<ArcSwap<KeyRules>> = Lazy::new(|| ArcSwap::from_pointee(KeyRules::default()));
+
+#[derive(Default)]
+struct KeySettings {
+    transform: Option<KeyTransform>,
+    case_insensitive: bool,
+    /// the key of every alias, by alias, with whether a deprecated alias was warned about.
+    aliases: BTreeMap<String, (String, Option<Arc<AtomicBool>>)>,
+}
+
+/// the normalization of the process-wide keys.
+#[derive(Default)]
+struct KeyRules {
+    transform: Option<KeyTransform>,
+    case_insensitive: bool,
+    /// the most specific alias first, so `a.b` is resolved before `a`.
+    aliases: Vec<Alias>,
+}
+
+/// an alias with its key, both normalized.
+struct Alias {
+    /// the alias as it was registered.
+    name: String,
+    alias: String,
+    key: String,
+    /// set once a deprecated alias was warned about, None for the other aliases.
+    warned: Option<Arc<AtomicBool>>,
+}
+
+impl KeyRules {
+    fn new(settings: &KeySettings) -> KeyRules {
+        let mut rules = KeyRules { transform: settings.transform.clone(), case_insensitive: settings.case_insensitive, aliases: Vec::new() };
+        rules.aliases = settings
+            .aliases
+            .iter()
+            .map(|(name, (key, warned))| Alias { name: name.clone(), alias: rules.normalize_segments(name), key: rules.normalize_segments(key), warned: warned.clone() })
+            .collect();
+        rules.aliases.sort_by_key(|alias| Reverse(split_path(&alias.alias).len()));
+        rules
+    }
+
+    /// whether the keys are used as they are given.
+    fn is_identity(&self) -> bool {
+        self.transform.is_none() && !self.case_insensitive && self.aliases.is_empty()
+    }
+
+    /// a dotted key with the transform and the case applied to every segment.
+    fn normalize_segments(&self, key: &str) -> String {
+        if self.transform.is_none() && !self.case_insensitive {
+            return key.to_string();
+        }
+        key.split('.').map(|segment| normalize_segment(segment, self.transform.as_ref(), self.case_insensitive)).collect::<Vec<_>>().join(".")
+    }
+
+    fn normalize_key(&self, key: &str) -> String {
+        if self.is_identity() {
+            return key.to_string();
+        }
+        let key = self.normalize_segments(key);
+        for alias in &self.aliases {
+            let resolved = if key == alias.alias {
+                alias.key.clone()
+            } else if let Some(rest) = key.strip_prefix(&alias.alias).and_then(|rest| rest.strip_prefix('.')) {
+                format!("{}.{}", alias.key, rest)
+            } else {
+                continue;
+            };
+            alias.used();
+            return resolved;
+        }
+        key
+    }
+}
+
+impl Alias {
+    /// warns about a deprecated alias the first time it is used.
+    fn used(&self) {
+        if let Some(warned) = &self.warned {
+            if !warned.swap(true, Ordering::SeqCst) {
+                warn_deprecated(&self.name);
+            }
+        }
+    }
+}
+
+/// changes the key settings and rebuilds the rules of the getters.
+fn change_settings(change: impl FnOnce(&mut KeySettings)) {
+    let mut settings = SETTINGS.lock().unwrap();
+    change(&mut settings);
+    RULES.store(Arc::new(KeyRules::new(&settings)));
+}
+
+/// Make the keys of the configuration case-insensitive, like viper does.
+/// the keys of every file and source read afterwards are lowercased, and so are the keys given to the getters,
+/// so `get_string("Database.Host")` reads `database.host`. when two keys only differ by case the last one loaded wins.
+/// call it before read_config, it fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set_case_insensitive(true).unwrap();
+/// confmap::read_config();
+/// ```
+pub fn set_case_insensitive(enabled: bool) -> Result<(), FrozenError> {
+    check()?;
+    change_settings(|settings| settings.case_insensitive = enabled);
+    Ok(())
+}
+
+/// Set a transform applied to every key of the configuration, segment by segment, both when it is loaded and when it is read.
+/// e.g. with `|key| key.replace('-', "_")` a toml file setting `enable-tls` and a json file setting `enable_tls`
+/// set the same key, which `get_bool("enable-tls")` and `get_bool("enable_tls")` both read.
+/// it runs before the keys are lowercased by set_case_insensitive. call it before read_config, it fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set_key_transform(|key| key.replace('-', "_")).unwrap();
+/// confmap::read_config();
+/// ```
+pub fn set_key_transform<F>(transform: F) -> Result<(), FrozenError>
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    check()?;
+    change_settings(|settings| settings.transform = Some(Arc::new(transform)));
+    Ok(())
+}
+
+/// Register an alias of a key, so an old key name keeps working after the configuration is reorganized.
+/// the getters read the key when given the alias, and a file or source still setting the alias sets the key,
+/// unless it sets the key too. when aliases are nested the most specific one applies.
+/// deprecation_report lists the sources still using an alias.
+/// fails once the configuration is frozen, an alias would redirect the getters of its key.
+/// # Example
+/// ```
+/// confmap::register_alias("verbose", "logging.verbose").unwrap();
+/// // reads logging.verbose, which a file can set as "verbose"
+/// confmap::get_bool("verbose");
+/// ```
+pub fn register_alias(alias: &str, key: &str) -> Result<(), FrozenError> {
+    check()?;
+    change_settings(|settings| {
+        settings.aliases.insert(alias.to_string(), (key.to_string(), None));
+    });
+    Ok(())
+}
+
+/// registers an alias which warns the first time it is used, see deprecate_key.
+pub(crate) fn register_deprecated_alias(alias: &str, key: &str) -> Result<(), FrozenError> {
+    check()?;
+    change_settings(|settings| {
+        settings.aliases.insert(alias.to_string(), (key.to_string(), Some(Arc::new(AtomicBool::new(false)))));
+    });
+    Ok(())
+}
+
+/// every alias with its key.
+pub(crate) fn aliases() -> BTreeMap<String, String> {
+    SETTINGS.lock().unwrap().aliases.iter().map(|(alias, (key, _))| (alias.clone(), key.clone())).collect()
+}
+
+/// moves the values set at an alias to its key, unless the key is set too.
+pub(crate) fn apply_aliases(map: &mut Map<String, Value>) {
+    let rules = RULES.load();
+    for alias in &rules.aliases {
+        let path = split_path(&alias.alias);
+        if lookup_path(map, &path).is_none() {
+            continue;
+        }
+        alias.used();
+        let value = remove_path(map, &path);
+        if let (Some(value), None) = (value, lookup_path(map, &split_path(&alias.key))) {
+            insert_path(map, &split_path(&alias.key), value);
+        }
+    }
+}
+
+/// how a view normalizes the keys given to its getters, like the keys of the map it reads were.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum KeyMode {
+    /// the keys are used as they are given.
+    #[default]
+    Verbatim,
+    /// the keys are lowercased, see ConfigBuilder::case_insensitive.
+    Lowercase,
+    /// the keys get the transform and the case of the process-wide configuration.
+    Process,
+}
+
+impl KeyMode {
+    /// a key relative to a section of the configuration. the aliases are not applied, they name the whole path of a key.
+    pub(crate) fn normalize(self, key: &str) -> String {
+        match self {
+            KeyMode::Verbatim => key.to_string(),
+            KeyMode::Lowercase => key.to_lowercase(),
+            KeyMode::Process => RULES.load().normalize_segments(key),
+        }
+    }
+}
+
+/// the key a getter looks up in the process-wide configuration, an alias is replaced by its key.
+pub(crate) fn normalize_key(key: &str) -> String {
+    RULES.load().normalize_key(key)
+}
+
+/// the keys of a map loaded into the process-wide configuration.
+pub(crate) fn normalize_map(map: Map<String, Value>) -> Map<String, Value> {
+    let rules = RULES.load();
+    if rules.transform.is_none() && !rules.case_insensitive {
+        return map;
+    }
+    rewrite_map(map, &|key| normalize_segment(key, rules.transform.as_ref(), rules.case_insensitive))
+}
+
+fn normalize_segment(segment: &str, transform: Option<&KeyTransform>, case_insensitive: bool) -> String {
+    let segment = match transform {
+        Some(transform) => transform(segment),
+        None => segment.to_string(),
+    };
+    normalize_key_with(&segment, case_insensitive)
+}
+
+pub(crate) fn normalize_key_with(key: &str, case_insensitive: bool) -> String {
+    if case_insensitive {
+        key.to_lowercase()
+    } else {
+        key.to_string()
+    }
+}
+
+pub(crate) fn normalize_map_with(map: Map<String, Value>, case_insensitive: bool) -> Map<String, Value> {
+    if !case_insensitive {
+        return map;
+    }
+    rewrite_map(map, &|key| key.to_lowercase())
+}
+
+/// rewrites every key of a map, nested ones included.
+fn rewrite_map(map: Map<String, Value>, rewrite: &dyn Fn(&str) -> String) -> Map<String, Value> {
+    map.into_iter().map(|(key, value)| (rewrite(&key), rewrite_value(value, rewrite))).collect()
+}
+
+fn rewrite_value(value: Value, rewrite: &dyn Fn(&str) -> String) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(rewrite_map(map, rewrite)),
+        Value::Array(arr) => Value::Array(arr.into_iter().map(|element| rewrite_value(element, rewrite)).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_keys_are_lowercased() {
+        let map: Map<String, Value> = serde_json::from_str(r#"{"Database": {"Host": "h", "Replicas": [{"Port": 1}]}}"#).unwrap();
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"database": {"host": "h", "replicas": [{"port": 1}]}}"#).unwrap();
+        assert_eq!(expected, normalize_map_with(map, true));
+        assert_eq!("database.host", normalize_key_with("Database.Host", true));
+    }
+
+    #[test]
+    fn transformed_keys_are_rewritten() {
+        let map: Map<String, Value> = serde_json::from_str(r#"{"enable-tls": true, "server": {"max-conns": 1}}"#).unwrap();
+        let transform: KeyTransform = Arc::new(|key: &str| key.replace('-', "_"));
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"enable_tls": true, "server": {"max_conns": 1}}"#).unwrap();
+        assert_eq!(expected, rewrite_map(map, &|key| normalize_segment(key, Some(&transform), false)));
+    }
+
+    #[test]
+    fn the_most_specific_alias_wins() {
+        let mut settings = KeySettings::default();
+        settings.aliases.insert("a".to_string(), ("x".to_string(), None));
+        settings.aliases.insert("a.b".to_string(), ("y.z".to_string(), None));
+        let rules = KeyRules::new(&settings);
+        assert_eq!("y.z.c", rules.normalize_key("a.b.c"));
+        assert_eq!("x.d", rules.normalize_key("a.d"));
+        assert_eq!("ab", rules.normalize_key("ab"));
+        assert!(KeyRules::new(&KeySettings::default()).is_identity());
+    }
+
+    #[test]
+    fn aliases_resolve_to_their_key() {
+        let _guard = crate::tests::lock_global_state();
+        register_alias("aliasTestVerbose", "aliasTestLogging.verbose").unwrap();
+        assert_eq!("aliasTestLogging.verbose", normalize_key("aliasTestVerbose"));
+        let mut map: Map<String, Value> = serde_json::from_str(r#"{"aliasTestVerbose": true}"#).unwrap();
+        apply_aliases(&mut map);
+        change_settings(|settings| {
+            settings.aliases.remove("aliasTestVerbose");
+        });
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"aliasTestLogging": {"verbose": true}}"#).unwrap();
+        assert_eq!(expected, map);
+    }
+}
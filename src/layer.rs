@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::defaults::read_defaults;
+use crate::diff::flatten;
+use crate::freeze::{check, FrozenError};
+use crate::keys::apply_aliases;
+use crate::merge::fill_missing;
+use crate::overrides::{apply_runtime_changes, read_overrides};
+use crate::pattern::{lookup_path, split_path};
+use crate::reload::ReloadError;
+use crate::{MergeStrategy, CONFIGS};
+
+/// the kinds of sources the configuration is layered from.
+/// when several layers set a key the one with the highest precedence wins, see set_precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Layer {
+    /// the config file and the files and folders added to it.
+    File,
+    /// configuration services, databases and object stores.
+    Remote,
+    /// environment variables.
+    Env,
+    /// any other Source.
+    Custom,
+    /// the default values set by the application.
+    Default,
+    /// command line flags.
+    Flag,
+    /// the values set explicitly by the application code.
+    Override,
+}
+
+/// the default precedence, highest first.
+const DEFAULT_PRECEDENCE: [Layer; 7] =
+    [Layer::Override, Layer::Flag, Layer::Env, Layer::Remote, Layer::Custom, Layer::File, Layer::Default];
+
+/// the layers the effective configuration was resolved from, in load order.
+static LAYERS: Lazy<Mutex<Vec<LoadedLayer>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// the name of the source each leaf key of the configuration came from, by dotted key.
+static ORIGINS: Lazy<Mutex<BTreeMap<String, String>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+/// held while a configuration is published, so the values, origins and layers of concurrent loads are never mixed.
+static PUBLISHING: Mutex<()> = Mutex::new(());
+static DISABLED: Lazy<Mutex<BTreeSet<Layer>>> = Lazy::new(|| Mutex::new(BTreeSet::new()));
+static PRECEDENCE: Lazy<Mutex<Vec<Layer>>> = Lazy::new(|| Mutex::new(DEFAULT_PRECEDENCE.to_vec()));
+
+/// the values one source provided.
+#[derive(Debug, Clone)]
+pub(crate) struct LoadedLayer {
+    layer: Layer,
+    origin: String,
+    values: Map<String, Value>,
+    strategy: MergeStrategy,
+}
+
+/// the value a source sets for a key.
+#[derive(Debug, Clone)]
+pub(crate) struct LayerValue {
+    pub(crate) layer: Layer,
+    pub(crate) origin: String,
+    pub(crate) value: Value,
+    pub(crate) disabled: bool,
+}
+
+/// a configuration being loaded off to the side, published to the getters once it is complete.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Loaded {
+    layers: Vec<LoadedLayer>,
+    failures: Vec<ReloadError>,
+}
+
+impl Loaded {
+    /// the layers the getters currently see.
+    pub(crate) fn current() -> Loaded {
+        Loaded { layers: LAYERS.lock().unwrap().clone(), failures: Vec::new() }
+    }
+
+    /// adds the values loaded from the source named `origin` on top of the previous ones.
+    pub(crate) fn push(&mut self, layer: Layer, origin: &str, values: Map<String, Value>, strategy: MergeStrategy) {
+        self.layers.push(LoadedLayer { layer, origin: origin.to_string(), values, strategy });
+    }
+
+    /// records that the source named `origin` could not be loaded.
+    pub(crate) fn fail(&mut self, origin: &str, message: String) {
+        self.failures.push(ReloadError { source: origin.to_string(), message });
+    }
+
+    /// the sources which could not be loaded, in load order.
+    pub(crate) fn failures(&self) -> &[ReloadError] {
+        &self.failures
+    }
+
+    /// drops the values of the source named `origin`, to push them again.
+    pub(crate) fn remove(&mut self, layer: Layer, origin: &str) {
+        self.layers.retain(|loaded| loaded.layer != layer || loaded.origin != origin);
+    }
+
+    /// replaces the values of the source named `origin`, keeping its place in the load order.
+    /// returns false when the source was not loaded or already had these values.
+    pub(crate) fn replace(&mut self, layer: Layer, origin: &str, values: Map<String, Value>) -> bool {
+        match self.layers.iter_mut().find(|loaded| loaded.layer == layer && loaded.origin == origin) {
+            Some(loaded) if loaded.values != values => {
+                loaded.values = values;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// merges the enabled layers from the lowest precedence to the highest, with the source each leaf key came from.
+    /// the sources of the same layer are merged in load order.
+    /// the values set at an alias are moved to its key.
+    /// the keys given to remove and set are applied last.
+    pub(crate) fn resolve(&self) -> (Map<String, Value>, BTreeMap<String, String>) {
+        let (mut values, mut origins) = self.resolve_with(&precedence(), &DISABLED.lock().unwrap().clone(), true);
+        apply_runtime_changes(&mut values, &mut origins);
+        (values, origins)
+    }
+
+    /// the configuration of these layers with the default precedence and every layer enabled,
+    /// whatever set_precedence, disable_layer and register_alias did to the process-wide one.
+    pub(crate) fn standalone_values(&self) -> Map<String, Value> {
+        self.resolve_with(&DEFAULT_PRECEDENCE, &BTreeSet::new(), false).0
+    }
+
+    fn resolve_with(&self, precedence: &[Layer], disabled: &BTreeSet<Layer>, aliases: bool) -> (Map<String, Value>, BTreeMap<String, String>) {
+        let (defaults, mut enabled): (Vec<&LoadedLayer>, Vec<&LoadedLayer>) =
+            self.layers.iter().filter(|loaded| !disabled.contains(&loaded.layer)).partition(|loaded| loaded.layer == Layer::Default);
+        enabled.sort_by_key(|loaded| rank(precedence, loaded.layer));
+        let mut values = Map::new();
+        let mut origins = BTreeMap::new();
+        for loaded in enabled {
+            let mut layer_values = loaded.values.clone();
+            if aliases {
+                apply_aliases(&mut layer_values);
+            }
+            loaded.strategy.merge(&mut values, layer_values.clone());
+            let leaves = flatten(&values);
+            origins.retain(|key: &String, _| leaves.contains_key(key));
+            for key in flatten(&layer_values).into_keys() {
+                origins.insert(key, loaded.origin.clone());
+            }
+        }
+        // the defaults only fill the keys no other layer sets, whatever the strategy of the layers above them
+        for loaded in defaults.into_iter().rev() {
+            let mut layer_values = loaded.values.clone();
+            if aliases {
+                apply_aliases(&mut layer_values);
+            }
+            fill_missing(&mut values, layer_values);
+            for key in flatten(&values).into_keys() {
+                origins.entry(key).or_insert_with(|| loaded.origin.clone());
+            }
+        }
+        (values, origins)
+    }
+
+    /// the names of every source loaded, in load order.
+    pub(crate) fn origins(&self) -> Vec<String> {
+        let mut origins: Vec<String> = Vec::new();
+        for loaded in &self.layers {
+            if !origins.contains(&loaded.origin) {
+                origins.push(loaded.origin.clone());
+            }
+        }
+        origins
+    }
+
+    /// the paths of the config files loaded, in load order.
+    pub(crate) fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for loaded in self.layers.iter().filter(|loaded| loaded.layer == Layer::File) {
+            let file = PathBuf::from(&loaded.origin);
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        files
+    }
+
+    /// the names of the sources which set a dotted key, in load order, disabled layers included.
+    pub(crate) fn origins_setting(&self, key: &str) -> Vec<String> {
+        let path = split_path(key);
+        self.layers
+            .iter()
+            .filter(|loaded| lookup_path(&loaded.values, &path).is_some())
+            .map(|loaded| loaded.origin.clone())
+            .collect()
+    }
+
+    /// the value every source sets for a dotted key, from the highest precedence to the lowest, disabled layers included.
+    pub(crate) fn values_of(&self, key: &str) -> Vec<LayerValue> {
+        let disabled = DISABLED.lock().unwrap().clone();
+        let precedence = precedence();
+        let path = split_path(key);
+        let mut values: Vec<LayerValue> = self
+            .layers
+            .iter()
+            .filter_map(|loaded| {
+                let mut layer_values = loaded.values.clone();
+                apply_aliases(&mut layer_values);
+                lookup_path(&layer_values, &path).map(|value| LayerValue {
+                    layer: loaded.layer,
+                    origin: loaded.origin.clone(),
+                    value: value.clone(),
+                    disabled: disabled.contains(&loaded.layer),
+                })
+            })
+            .collect();
+        values.reverse();
+        values.sort_by_key(|value| std::cmp::Reverse(rank(&precedence, value.layer)));
+        values
+    }
+
+    /// the effective configuration of these layers.
+    pub(crate) fn values(&self) -> Map<String, Value> {
+        self.resolve().0
+    }
+
+    /// makes these layers the configuration the getters see, then notifies the subscribers of the changed keys.
+    /// the whole map is built before it is swapped in, and concurrent publishes take turns. the defaults, the merged maps
+    /// and the overrides are read again once it is their turn, like the keys given to set and remove,
+    /// so the ones changed while a reload was loading its sources are not lost.
+    /// returns the configuration it replaced and the one published, it is refused once the configuration is frozen.
+    pub(crate) fn publish(self) -> Result<Published, FrozenError> {
+        let publishing = PUBLISHING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.publish_locked(publishing)
+    }
+
+    /// changes the layers the getters currently see and publishes them, without letting another publish in between.
+    pub(crate) fn update(change: impl FnOnce(&mut Loaded)) {
+        let publishing = PUBLISHING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut loaded = Loaded::current();
+        change(&mut loaded);
+        if let Err(e) = loaded.publish_locked(publishing) {
+            println!("{}, the change is ignored", e);
+        }
+    }
+
+    fn publish_locked(mut self, publishing: MutexGuard<'_, ()>) -> Result<Published, FrozenError> {
+        check()?;
+        read_defaults(&mut self);
+        read_overrides(&mut self);
+        let (values, origins) = self.resolve();
+        #[cfg(feature = "shared_memory")]
+        crate::shared::republish(&values);
+        let values = Arc::new(values);
+        let old = CONFIGS.swap(values.clone());
+        *ORIGINS.lock().unwrap() = origins;
+        *LAYERS.lock().unwrap() = self.layers;
+        #[cfg(feature = "tokio")]
+        crate::changes::publish(&values);
+        let published = Published { old, new: values };
+        crate::subscribe::queue(published.clone());
+        // the subscribers may change the configuration themselves.
+        drop(publishing);
+        crate::subscribe::deliver();
+        Ok(published)
+    }
+}
+
+/// the configuration a publish replaced, and the one it published.
+#[derive(Clone)]
+pub(crate) struct Published {
+    pub(crate) old: Arc<Map<String, Value>>,
+    pub(crate) new: Arc<Map<String, Value>>,
+}
+
+/// the rank of a layer, higher wins: the first layer of `precedence` has the highest, missing layers the lowest.
+fn rank(precedence: &[Layer], layer: Layer) -> usize {
+    match precedence.iter().position(|&ranked| ranked == layer) {
+        Some(position) => DEFAULT_PRECEDENCE.len() + precedence.len() - position,
+        None => DEFAULT_PRECEDENCE.len() - DEFAULT_PRECEDENCE.iter().position(|&ranked| ranked == layer).unwrap_or(0),
+    }
+}
+
+/// this function will return the layers from the highest precedence to the lowest.
+/// by default: Override, Flag, Env, Remote, Custom, File, Default.
+/// # Example
+/// ```
+/// assert_eq!(confmap::Layer::Override, confmap::precedence()[0]);
+/// ```
+pub fn precedence() -> Vec<Layer> {
+    PRECEDENCE.lock().unwrap().clone()
+}
+
+/// Set the precedence of the layers, highest first, and resolve the configuration again.
+/// a value of a layer replaces the values of the layers after it, whatever the order the sources were loaded in.
+/// the layers left out keep their default order, below the listed ones. fails once the configuration is frozen.
+/// # Example
+/// ```
+/// use confmap::Layer;
+///
+/// // the config files win over the environment variables
+/// confmap::set_precedence(&[Layer::Override, Layer::Flag, Layer::File, Layer::Env, Layer::Remote]).unwrap();
+/// ```
+pub fn set_precedence(order: &[Layer]) -> Result<(), FrozenError> {
+    check()?;
+    *PRECEDENCE.lock().unwrap() = order.to_vec();
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// Disable a layer of the configuration at runtime.
+/// the configuration is resolved again without the sources of that layer, without reading them again,
+/// which helps to find out whether, e.g., an environment variable is behind an unexpected value.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::disable_layer(confmap::Layer::Env).unwrap();
+/// ```
+pub fn disable_layer(layer: Layer) -> Result<(), FrozenError> {
+    check()?;
+    DISABLED.lock().unwrap().insert(layer);
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// Enable a layer disabled by disable_layer again. fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::enable_layer(confmap::Layer::Env).unwrap();
+/// ```
+pub fn enable_layer(layer: Layer) -> Result<(), FrozenError> {
+    check()?;
+    DISABLED.lock().unwrap().remove(&layer);
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// the sources the value of a key came from, a section can come from several sources.
+pub(crate) fn origin_of(key: &str) -> Option<String> {
+    let origins = ORIGINS.lock().unwrap();
+    if let Some(origin) = origins.get(key) {
+        return Some(origin.clone());
+    }
+    let prefix = format!("{}.", key);
+    let mut found: Vec<&str> = Vec::new();
+    for (_, origin) in origins.iter().filter(|(leaf, _)| leaf.starts_with(&prefix)) {
+        if !found.contains(&origin.as_str()) {
+            found.push(origin);
+        }
+    }
+    Some(found.join(", ")).filter(|origins| !origins.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins_follow_the_last_source() {
+        let mut loaded = Loaded::default();
+        loaded.push(Layer::File, "file", serde_json::from_str(r#"{"db": {"host": "a", "port": 1}, "x": 1}"#).unwrap(), MergeStrategy::Replace);
+        loaded.push(Layer::Custom, "custom", serde_json::from_str(r#"{"db": {"host": "b"}}"#).unwrap(), MergeStrategy::Replace);
+        let expected: BTreeMap<String, String> =
+            [("db.host", "custom"), ("x", "file")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(expected, loaded.resolve().1);
+    }
+
+    #[test]
+    fn defaults_fill_the_keys_the_files_leave_out() {
+        let mut loaded = Loaded::default();
+        loaded.push(Layer::Default, "defaults", serde_json::from_str(r#"{"db": {"host": "localhost", "port": 5432}}"#).unwrap(), MergeStrategy::DeepMerge);
+        loaded.push(Layer::File, "file", serde_json::from_str(r#"{"db": {"host": "h"}}"#).unwrap(), MergeStrategy::Replace);
+        let (values, origins) = loaded.resolve_with(&DEFAULT_PRECEDENCE, &BTreeSet::new(), false);
+        assert_eq!(serde_json::json!({"db": {"host": "h", "port": 5432}}), Value::Object(values));
+        assert_eq!(Some(&"defaults".to_string()), origins.get("db.port"));
+        assert_eq!(Some(&"file".to_string()), origins.get("db.host"));
+    }
+
+    #[test]
+    fn layers_are_ranked_by_precedence() {
+        let order = [Layer::Flag, Layer::File];
+        assert!(rank(&order, Layer::Flag) > rank(&order, Layer::File));
+        assert!(rank(&order, Layer::File) > rank(&order, Layer::Override));
+        assert!(rank(&order, Layer::Env) > rank(&order, Layer::Default));
+        assert!(rank(&DEFAULT_PRECEDENCE, Layer::Env) > rank(&DEFAULT_PRECEDENCE, Layer::File));
+    }
+}
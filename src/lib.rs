@@ -8,74 +8,204 @@
 //! example:
 //! put a json format file in your project folder like this:
 //!
-//!         config.json
-//!         {
-//!             "testGetString": "YesMan",
-//!             "testGetInt64": 43,
-//!             "testGetStringArray": [
-//!                 "+44 1234567",
-//!                 "+44 2345678"
-//!             ]
-//!         }
+//! ```text
+//! config.json
+//! {
+//!     "testGetString": "YesMan",
+//!     "testGetInt64": 43,
+//!     "testGetStringArray": [
+//!         "+44 1234567",
+//!         "+44 2345678"
+//!     ]
+//! }
+//! ```
 //!
 //! add dependency in Cargo.toml:
 //!
-//!     [dependencies]
-//!
-//!     confmap = "1.0.0"
+//! ```toml
+//! [dependencies]
+//! confmap = "1.0.0"
+//! ```
 //!
 //! in your project main.rs:
 //!
-//!     use confmap;
-//!
-//!     fn main() {
-//!
-//!         confmap::add_config_path(path_str);
-//!
-//!         confmap::set_config_name("config.json");
+//! ```no_run
+//! use confmap;
 //!
-//!         confmap::read_config();
+//! confmap::add_config_path("/etc/myapp");
+//! confmap::set_config_name("config.json");
+//! confmap::read_config();
+//! assert_eq!(Some("YesMan".to_string()), confmap::get_string("testGetString"));
+//! assert_eq!(Some(43), confmap::get_int64("testGetInt64"));
+//! assert_eq!(Some(vec!["+44 1234567".to_string(), "+44 2345678".to_string()]), confmap::get_string_array("testGetStringArray"));
+//! ```
 //!
-//!         assert_eq!(Some("YesMan".to_string()), confmap::get_string("testGetString"));
+//! a nested value is read with a dotted key, e.g. `confmap::get_string("database.primary.host")`,
+//! and an array element with its index, e.g. `confmap::get_string("servers[0].host")`.
 //!
-//!         assert_eq!(Some(43), confmap::get_int64("testGetInt64"));
+//! a multi-command CLI can give every subcommand its own settings on top of the shared ones:
 //!
-//!         assert_eq!(Some(vec!["+44 1234567".to_string(), "+44 2345678".to_string()]), confmap::get_string_array("testGetStringArray"));
+//! ```no_run
+//! use confmap::ConfigRead;
 //!
-//!     }
+//! // resolves "commands.serve.port" first, then "port"
+//! let port = confmap::scope_for_subcommand("serve").get_int64("port");
+//! ```
 
+use std::collections::HashMap;
 use std::env;
-use std::error::Error;
+use std::fmt;
 use std::fs;
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::net::{IpAddr, SocketAddr};
+use std::num::{NonZeroU32, NonZeroU64};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use arc_swap::ArcSwap;
 use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
 use serde_json::{Map, Value};
 
-struct ConfigSerde;
+use getters::ReadOptions;
+use layer::Loaded;
+use pattern::lookup_key;
 
-static mut CONFIG_NAME: String = String::new();
-static mut CONFIG_PATH: String = String::new();
-static CONFIGS: Lazy<Arc<Mutex<Map<String, Value>>>> = Lazy::new(|| {
-    let m = Map::new();
-    Arc::new(Mutex::new(m))
-});
+mod bytes;
+#[cfg(feature = "tokio")]
+mod changes;
+mod coerce;
+mod config;
+mod crash;
+mod defaults;
+mod deprecation;
+mod diff;
+mod enums;
+mod environment;
+mod explain;
+mod format;
+mod freeze;
+mod getters;
+mod guard;
+mod import;
+mod include;
+mod keys;
+mod layer;
+mod merge;
+mod metadata;
+mod overlay;
+mod overrides;
+mod pattern;
+mod polling;
+mod project;
+mod query;
+mod rate;
+mod redact;
+mod refresh;
+mod reload;
+mod remote;
+mod require;
+mod reserved;
+mod schema;
+#[cfg(feature = "shared_memory")]
+mod shared;
+#[cfg(feature = "reload_signal")]
+mod signal;
+mod scheme;
+mod scope;
+mod scoped;
+mod section;
+mod size;
+mod snapshot;
+mod source;
+mod subscribe;
+mod summary;
+mod write;
 
-impl ConfigSerde {
-    fn parse_value(value_ref: &Value) -> Value {
-        value_ref.clone()
-    }
+#[cfg(feature = "tokio")]
+pub use changes::changes;
+pub use coerce::{set_coercion, set_mixed_arrays, MixedArrays};
+pub use config::{Config, ConfigBuilder};
+pub use crash::{crash_context, fingerprint, install_panic_hook};
+pub use defaults::{set_default, set_defaults_from, set_defaults_from_struct, write_default_config, DefaultsError};
+pub use deprecation::{deprecate_key, deprecation_report, on_deprecated_key, DeprecatedKey, DeprecationHit};
+pub use diff::ConfigDiff;
+pub use environment::EnvSource;
+pub use explain::{explain, Explanation, KeySource};
+pub use format::Format;
+pub use freeze::{freeze, is_frozen, FrozenError};
+pub use getters::{ConfigError, ConfigRead};
+pub use guard::{set_change_guard, ChangeGuard, ChangeReview};
+pub use import::{import_from_config_rs, import_from_dotenv};
+pub use keys::{register_alias, set_case_insensitive, set_key_transform};
+pub use layer::{disable_layer, enable_layer, precedence, set_precedence, Layer};
+pub use merge::{set_array_merge, ArrayMerge, MergeStrategy};
+pub use metadata::{key_metadata, set_key_metadata, KeyMetadata};
+pub use overlay::{set_host_overlays, set_local_overrides, set_profile, set_role, set_user_config};
+pub use overrides::{merge, merge_value, remove, set, set_overrides};
+pub use polling::watch_config_polling;
+pub use project::project;
+pub use query::{all_settings, find_keys_with_value, flatten, get_matching, iter, iter_flat, keys, search_keys, Entries};
+pub use rate::{ParseRateError, Rate};
+pub use redact::{mark_secret, redacted, REDACTED};
+pub use refresh::set_refresh_interval;
+pub use reload::{on_reload_error, reload, set_reload_debounce, set_reload_policy, ReloadError, ReloadErrorHandler, ReloadPolicy};
+pub use remote::{KvClient, KvFetch, KvPair, KvSource, ObjectFetch, ObjectStoreClient, S3Source, SqlClient, SqlLayout, SqlSource, ZnodeClient, ZookeeperSource};
+pub use require::{require, require_string, RequiredError};
+pub use reserved::reserve_prefix;
+pub use scheme::{add_config_url, register_scheme, SchemeHandler};
+pub use scope::{scope_for_subcommand, Scope};
+pub use scoped::{override_for_scope, OverrideGuard};
+pub use section::ConfigSection;
+#[cfg(feature = "shared_memory")]
+pub use shared::{share_config, SharedSource};
+#[cfg(feature = "reload_signal")]
+pub use signal::reload_on_signal;
+pub use snapshot::{snapshot, ConfigSnapshot};
+pub use source::{add_source, watch_sources, ChangeNotifier, FileSource, Source, SourceError};
+pub use subscribe::{subscribe, unsubscribe, KeyChange, Subscription};
+pub use summary::summary;
+pub use write::{safe_write_config, write_config, write_config_as, write_config_to};
 
-    fn read_config(config_path: &str) -> Result<Map<String, Value>, Box<dyn Error>> {
-        println!("reading file {}", config_path);
-        let config = fs::read_to_string(config_path)?;
-        let parsed: Map<String, Value> = serde_json::from_str(config.as_str())?;
-        let result = parsed
-            .into_iter()
-            .map(|(k, v)| (k, ConfigSerde::parse_value(&v)))
-            .collect();
-        Ok(result)
+static FILE_SETTINGS: Mutex<FileSettings> = Mutex::new(FileSettings { name: String::new(), path: String::new() });
+static WORKSPACE_MODE: AtomicBool = AtomicBool::new(false);
+static CONFIG_FILES: Lazy<Mutex<Vec<ConfigLocation>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// the name of the config file and the folder it is in, set by set_config_name and add_config_path.
+#[derive(Debug)]
+struct FileSettings {
+    name: String,
+    /// empty, or ends with the path separator.
+    path: String,
+}
+
+/// a config file or a folder of config fragments layered on top of the main config file.
+#[derive(Debug, Clone)]
+enum ConfigLocation {
+    File(PathBuf),
+    Dir(PathBuf),
+    Glob(PathBuf),
+}
+/// the effective configuration, read by every getter without locking.
+/// a new configuration is swapped in as a whole, so a reader never sees a half-loaded one.
+static CONFIGS: Lazy<ArcSwap<Map<String, Value>>> = Lazy::new(|| ArcSwap::from_pointee(Map::new()));
+
+fn config_name() -> String {
+    FILE_SETTINGS.lock().unwrap().name.clone()
+}
+
+fn config_path() -> String {
+    FILE_SETTINGS.lock().unwrap().path.clone()
+}
+
+/// sets the folder of the config file, with a trailing path separator.
+fn set_config_path(path: &str) {
+    let mut path = path.to_string();
+    if !path.ends_with(std::path::MAIN_SEPARATOR) {
+        path.push(std::path::MAIN_SEPARATOR);
     }
+    FILE_SETTINGS.lock().unwrap().path = path;
 }
 
 /// Set filename.
@@ -85,386 +215,1919 @@ impl ConfigSerde {
 /// ```
 ///
 pub fn set_config_name(config_name: &str) {
-    unsafe { CONFIG_NAME = config_name.to_string(); }
+    FILE_SETTINGS.lock().unwrap().name = config_name.to_string();
 }
 
 /// Add path of the file.
-/// this will allow you to put config file in other path
+/// this will allow you to put config file in other path.
+/// a url such as `s3://bucket/myapp/config.json` or `env://MYAPP` is given to add_config_url instead,
+/// so `file:///etc/myapp/config.json` is a config file, like for add_config_url, and never a folder.
+/// a glob such as `conf/*.toml` or `plugins/**/*.json` adds every matching file instead, merged like add_config_file
+/// in the lexical order of their paths, the matches are looked up again by every read_config.
 /// # Example
-/// confmap::add_config_path("config.json");
+/// ```
+/// confmap::add_config_path("/etc/myapp");
+/// confmap::add_config_path("plugins/*.json");
 /// ```
 pub fn add_config_path(path: &str) {
-    unsafe {
-        #[cfg(target_family = "unix")]
-        if path.ends_with("/") {
-            CONFIG_PATH = path.to_string();
-        } else {
-            CONFIG_PATH = path.to_string() + "/";
-        }
-        #[cfg(target_family = "windows")]
-        if path.ends_with("\\") {
-            CONFIG_PATH = path.to_string();
-        } else {
-            CONFIG_PATH = path.to_string() + "\\";
+    if scheme::scheme_of(path).is_some() {
+        if let Err(e) = add_config_url(path) {
+            println!("failed to add config url {}: {}", path, e);
         }
+        return;
+    }
+    if include::is_glob(Path::new(path)) {
+        CONFIG_FILES.lock().unwrap().push(ConfigLocation::Glob(PathBuf::from(path)));
+        return;
     }
+    set_config_path(path);
+}
+
+/// Add a config file layered on top of the config file set by set_config_name.
+/// the files are loaded in the order they are added and deep-merged, so a later file overrides
+/// only the keys it sets, even inside nested objects. a relative path is relative to the folder given to add_config_path.
+/// # Example
+/// ```
+/// confmap::add_config_file("defaults.json");
+/// confmap::add_config_file("overrides.json");
+/// confmap::read_config();
+/// ```
+pub fn add_config_file(path: &str) {
+    CONFIG_FILES.lock().unwrap().push(ConfigLocation::File(PathBuf::from(path)));
+}
+
+/// Add a folder of config fragments, like the conf.d folders of nginx or systemd.
+/// every file of the folder with a known extension (`.json`, `.toml` with the toml feature) is loaded
+/// in the lexical order of the file names and deep-merged, so `20-tls.json` overrides `10-base.json`.
+/// the folder takes its place among the files added by add_config_file.
+/// # Example
+/// ```
+/// confmap::add_config_dir("conf.d/");
+/// confmap::read_config();
+/// ```
+pub fn add_config_dir(path: &str) {
+    CONFIG_FILES.lock().unwrap().push(ConfigLocation::Dir(PathBuf::from(path)));
 }
 
-/// this function read config file after file path and file name are given.
+/// Enable or disable workspace mode.
+/// in workspace mode read_config walks from the current directory up to the workspace root
+/// (the first folder containing `.git`) and merges every config file with the configured name found on the way.
+/// the file at the root is loaded first, so a nested package's file overrides it, like cargo or eslint do in a monorepo.
+/// # Example
+/// ```no_run
+/// confmap::set_config_name("tool.config.json");
+/// confmap::set_workspace_mode(true);
+/// confmap::read_config();
+/// ```
+pub fn set_workspace_mode(enabled: bool) {
+    WORKSPACE_MODE.store(enabled, Ordering::SeqCst);
+}
+
+/// this function read config file after file path and file name are given,
+/// then the sources added by add_source in order. every call reads all of them again and replaces the whole configuration.
+/// a document can declare the types of its keys in a `"$types"` section, e.g. `{"port": "integer", "db.hosts": "string[]", "debug": "boolean?"}`,
+/// a document which does not match its declarations is not loaded.
 /// you can use get_string, get_int64 ...etc, to get the value after config file is loaded by this function.
+/// once the configuration is frozen nothing is read, try_read_config returns the error instead of printing it.
 /// # Example
 /// ```
 /// confmap::read_config();
 /// ```
 pub fn read_config() {
-    if !unsafe { CONFIG_NAME.is_empty() } {
-        let path_buf = env::current_exe().expect("Failed to get executable path");
-        let paths = fs::read_dir(path_buf.parent().unwrap()).unwrap();
-        let mut is_found:bool;
-        unsafe{
-            let file_path = CONFIG_PATH.to_owned() + &CONFIG_NAME;
-            let path = Path::new(&file_path);
-            is_found = path.exists() && path.is_file();
+    if let Err(e) = try_read_config() {
+        println!("{}, read_config is ignored", e);
+    }
+}
+
+/// this function works like read_config, but fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::try_read_config().expect("failed to read the config");
+/// ```
+pub fn try_read_config() -> Result<(), FrozenError> {
+    read_config_with_strategies(MergeStrategy::Replace, MergeStrategy::DeepMerge)
+}
+
+/// this function works like read_config, but every file and source is merged with the given strategy.
+/// read_config replaces top-level keys for the config file and the sources, and deep-merges the files added by add_config_file.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::read_config_with(confmap::MergeStrategy::DeepMerge).unwrap();
+/// ```
+pub fn read_config_with(strategy: MergeStrategy) -> Result<(), FrozenError> {
+    read_config_with_strategies(strategy, strategy)
+}
+
+fn read_config_with_strategies(strategy: MergeStrategy, files_strategy: MergeStrategy) -> Result<(), FrozenError> {
+    freeze::check()?;
+    let mut loaded = Loaded::default();
+    load_configs(&mut loaded, strategy, files_strategy);
+    loaded.publish()?;
+    Ok(())
+}
+
+/// Reset the configuration to what it was before anything was loaded, e.g. between integration tests using different config files.
+/// the config name and path, the added files and sources, the defaults, the overrides, the merged maps
+/// and the keys given to set and remove are cleared, and the getters see an empty configuration.
+/// settings such as set_case_insensitive, set_precedence or set_profile are kept. fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set("scratch", 1).unwrap();
+/// confmap::reset().unwrap();
+/// assert!(!confmap::is_set("scratch"));
+/// ```
+pub fn reset() -> Result<(), FrozenError> {
+    freeze::check()?;
+    *FILE_SETTINGS.lock().unwrap() = FileSettings { name: String::new(), path: String::new() };
+    CONFIG_FILES.lock().unwrap().clear();
+    source::SOURCES.lock().unwrap().clear();
+    defaults::clear();
+    overrides::clear();
+    Loaded::default().publish()?;
+    Ok(())
+}
+
+/// reads the config file and the sources into `configs`, which is not yet visible to the getters.
+/// `strategy` merges the config file and the sources, `files_strategy` the overlays, files and folders added to it.
+fn load_configs(configs: &mut Loaded, strategy: MergeStrategy, files_strategy: MergeStrategy) {
+    defaults::read_defaults(configs);
+    if !config_name().is_empty() {
+        if WORKSPACE_MODE.load(Ordering::SeqCst) {
+            read_workspace_configs(configs, strategy, files_strategy);
+        } else {
+            read_config_file(configs, strategy, files_strategy);
         }
-        if !is_found {
-            for path in paths {
-                let path_str = path.unwrap().path();
-                let filename = path_str.file_name().unwrap().to_string_lossy();
-                unsafe {
-                    if filename == CONFIG_NAME.to_string() {
-                        #[cfg(target_family = "unix")]
-                        {
-                            CONFIG_PATH = path_str.clone().parent().unwrap().to_string_lossy().to_string() + "/";
-                        }
-                        #[cfg(target_family = "windows")]
-                        {
-                            CONFIG_PATH = path_str.clone().parent().unwrap().to_string_lossy().parse().unwrap() + "\\";
-                        }
-                        // CONFIG_NAME = filename.parse().unwrap();
-                        println!("file is found!!");
-                        is_found = true;
-                        break;
-                    } // else {
-                    //     println!("Got: {}, CONFIG_NAME: {:?}", filename, CONFIG_NAME.to_string());
-                    // }
-                }
+    }
+    read_config_files(configs, files_strategy);
+    if let Some(file) = overlay::user_config_file(&config_name()) {
+        init_lazy_configs(configs, &FileSource::new(file), files_strategy);
+    }
+    read_sources(configs, strategy);
+    overrides::read_overrides(configs);
+}
+
+fn read_config_file(configs: &mut Loaded, strategy: MergeStrategy, overlay_strategy: MergeStrategy) {
+    let path_buf = env::current_exe().expect("Failed to get executable path");
+    let paths = fs::read_dir(path_buf.parent().unwrap()).unwrap();
+    let name = config_name();
+    let file_path = config_path() + &name;
+    let path = Path::new(&file_path);
+    let mut is_found = path.exists() && path.is_file();
+    if !is_found {
+        for path in paths {
+            let path_str = path.unwrap().path();
+            let filename = path_str.file_name().unwrap().to_string_lossy();
+            if filename == name {
+                set_config_path(&path_str.parent().unwrap().to_string_lossy());
+                println!("file is found!!");
+                is_found = true;
+                break;
             }
         }
+    }
 
-        if is_found {
-            init_lazy_configs(&mut CONFIGS.lock().unwrap());
-        } else {
-            println!("file is not found");
+    if is_found {
+        let path = config_path() + &name;
+        read_main_file(configs, PathBuf::from(path), strategy, overlay_strategy);
+    } else {
+        println!("file is not found");
+    }
+}
+
+/// the process-wide configuration loaded by `read_config`.
+struct Global;
+
+impl ConfigRead for Global {
+    fn get(&self, key: &str) -> Option<Value> {
+        lookup_key(&CONFIGS.load(), &keys::normalize_key(key)).cloned()
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        ReadOptions::process()
+    }
+}
+
+/// reads a main config file, then deep-merges its overlays such as the file of the profile.
+fn read_main_file(configs: &mut Loaded, file: PathBuf, strategy: MergeStrategy, overlay_strategy: MergeStrategy) {
+    let overlays = overlay::overlay_files(&file);
+    init_lazy_configs(configs, &FileSource::new(file), strategy);
+    for overlay in overlays {
+        init_lazy_configs(configs, &FileSource::new(overlay), overlay_strategy);
+    }
+}
+
+fn read_workspace_configs(configs: &mut Loaded, strategy: MergeStrategy, overlay_strategy: MergeStrategy) {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+    let files = workspace_config_files(&current_dir, &config_name());
+    if files.is_empty() {
+        println!("file is not found");
+    }
+    for file in files {
+        read_main_file(configs, file, strategy, overlay_strategy);
+    }
+}
+
+fn read_config_files(configs: &mut Loaded, strategy: MergeStrategy) {
+    let locations = CONFIG_FILES.lock().unwrap().clone();
+    for location in locations {
+        let files = match location {
+            ConfigLocation::File(file) => vec![relative_to_config_path(file)],
+            ConfigLocation::Dir(dir) => fragment_files(&relative_to_config_path(dir)),
+            ConfigLocation::Glob(pattern) => include::glob_files(&relative_to_config_path(pattern)),
+        };
+        for file in files {
+            if file.is_file() {
+                init_lazy_configs(configs, &FileSource::new(file), strategy);
+            } else {
+                println!("file is not found: {}", file.display());
+            }
         }
     }
 }
 
-fn init_lazy_configs(input: &mut Map<String, Value>) {
-    let path = unsafe { CONFIG_PATH.to_string() + &CONFIG_NAME };
-    println!("init_lazy_configs path: {}", path);
-    match ConfigSerde::read_config(&path) {
-        Ok(configs) => {
-            for (k, v) in configs.iter() {
-                input.insert(k.clone(), v.clone()); // Assuming Value is Cloneable
+/// the files read_config would read now, with the ones it looks for which do not exist yet,
+/// so the polling watcher notices a config file or an overlay created after the first read.
+pub(crate) fn config_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let name = config_name();
+    if !name.is_empty() {
+        let mains = if WORKSPACE_MODE.load(Ordering::SeqCst) {
+            env::current_dir().map(|dir| workspace_config_files(&dir, &name)).unwrap_or_default()
+        } else {
+            vec![PathBuf::from(config_path() + &name)]
+        };
+        for main in mains {
+            files.extend(overlay::overlay_files(&main));
+            files.push(main);
+        }
+    }
+    for location in CONFIG_FILES.lock().unwrap().clone() {
+        match location {
+            ConfigLocation::File(file) => files.push(relative_to_config_path(file)),
+            ConfigLocation::Dir(dir) => {
+                let dir = relative_to_config_path(dir);
+                if dir.is_dir() {
+                    files.extend(fragment_files(&dir));
+                }
+                files.push(dir);
             }
+            ConfigLocation::Glob(pattern) => files.extend(include::glob_files(&relative_to_config_path(pattern))),
+        }
+    }
+    files.extend(overlay::user_config_file(&name));
+    files
+}
+
+fn relative_to_config_path(path: PathBuf) -> PathBuf {
+    if path.is_relative() && !config_path().is_empty() {
+        Path::new(&config_path()).join(path)
+    } else {
+        path
+    }
+}
+
+/// the config files of a fragment folder in lexical order, hidden files and unknown extensions are skipped.
+fn fragment_files(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("failed to read config folder {}: {}", dir.display(), e);
+            return Vec::new();
         }
-        Err(_e) => {
-            // not thing to do
+    };
+    let extensions: Vec<&str> = Format::all().iter().flat_map(|format| format.extensions()).copied().collect();
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')))
+        .filter(|path| path.extension().is_some_and(|ext| extensions.contains(&ext.to_string_lossy().as_ref())))
+        .collect();
+    files.sort();
+    files
+}
+
+/// collects the config files from the workspace root down to `start`, outermost first.
+fn workspace_config_files(start: &Path, config_name: &str) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in start.ancestors() {
+        let file = dir.join(config_name);
+        if file.is_file() {
+            files.push(file);
+        }
+        if dir.join(".git").exists() {
+            break;
         }
     }
-    println!("configs: {:?}", input);
+    files.reverse();
+    files
 }
 
-/// this function will return Option<String> when you put a key argument.
+fn read_sources(configs: &mut Loaded, strategy: MergeStrategy) {
+    let sources = source::SOURCES.lock().unwrap().clone();
+    for source in sources {
+        init_lazy_configs(configs, source.as_ref(), strategy);
+    }
+}
+
+/// loads a source, checks its inline types and drops its reserved keys.
+pub(crate) fn load_checked(source: &dyn Source) -> Result<Map<String, Value>, SourceError> {
+    let mut configs = keys::normalize_map(source.load()?);
+    schema::check_inline_types(&mut configs)?;
+    reserved::strip_reserved(&mut configs, &source.name());
+    Ok(configs)
+}
+
+fn init_lazy_configs(input: &mut Loaded, source: &dyn Source, strategy: MergeStrategy) {
+    match load_checked(source) {
+        Ok(configs) => input.push(source.layer(), &source.name(), configs, strategy),
+        Err(e) => {
+            println!("failed to load config: {}", e);
+            input.fail(&source.name(), e.to_string());
+        }
+    }
+    println!("configs: {:?}", redact::redact_map(&input.values()));
+}
+
+/// this function will return the value of the first key which is present.
 /// # Example
 /// ```
-/// confmap::get_string("testGetString");
+/// let host = confmap::get_first(&["db.read_host", "db.host", "host"]);
 /// ```
-pub fn get_string(key: &str) -> Option<String> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        value.as_str().map(|s| s.to_string())
-    } else {
-        None
-    }
+pub fn get_first(keys: &[&str]) -> Option<Value> {
+    Global.get_first(keys)
 }
 
-/// this function will return Option<Vec<String>> when you put a key argument.
+/// this function will return whether the key is present, even when its value is null, false or empty.
 /// # Example
 /// ```
-/// confmap::get_string_array("testGetStringArray");
+/// if confmap::is_set("feature.x") {
+///     println!("feature.x is configured");
+/// }
 /// ```
-pub fn get_string_array(key: &str) -> Option<Vec<String>> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        if let Value::Array(arr) = value {
-            let mut string_array = Vec::new();
-            for element in arr {
-                if let Value::String(s) = element {
-                    string_array.push(s.clone());
-                }
-            }
-            Some(string_array)
-        } else {
-            None
-        }
-    } else {
-        None
-    }
+pub fn is_set(key: &str) -> bool {
+    Global.is_set(key)
 }
 
-/// this function will return Option<i64> when you put a key argument.
+/// this function will return None when the key is missing, Some(None) when it is set to null
+/// and Some(Some(value)) otherwise.
 /// # Example
 /// ```
-/// confmap::get_int64("testGetInt64");
+/// match confmap::get_optional("feature.x") {
+///     None => println!("feature.x is not configured"),
+///     Some(None) => println!("feature.x is disabled"),
+///     Some(Some(value)) => println!("feature.x is {}", value),
+/// }
 /// ```
-pub fn get_int64(key: &str) -> Option<i64> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        match value {
-            Value::Number(n) => n.as_i64(),
-            _ => None,
-        }
-    } else {
-        None
-    }
+pub fn get_optional(key: &str) -> Option<Option<Value>> {
+    Global.get_optional(key)
 }
 
-/// this function will return Option<Vec<i64>> when you put a key argument.
+/// this function will return the value at a key deserialized into any serde type.
 /// # Example
 /// ```
-/// confmap::get_int64_array("testGetFloat64Array");
+/// use std::collections::HashMap;
+///
+/// let limits: Result<HashMap<String, u32>, confmap::ConfigError> = confmap::get_as("limits");
 /// ```
-pub fn get_int64_array(key: &str) -> Option<Vec<i64>> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        if let Value::Array(arr) = value {
-            let mut int64_array = Vec::new();
-            for element in arr {
-                if let Value::Number(n) = element {
-                    if let Some(int_value) = n.as_i64() {
-                        int64_array.push(int_value);
-                    }
-                }
-            }
-            Some(int64_array)
-        } else {
-            None
-        }
-    } else {
-        None
-    }
+pub fn get_as<T: DeserializeOwned>(key: &str) -> Result<T, ConfigError> {
+    Global.get_as(key)
 }
 
-/// this function will return Option<i32> when you put a key argument.
+/// this function will return the string value at a key parsed with `T::from_str`.
 /// # Example
 /// ```
-/// confmap::get_int32("testGetInt32");
+/// let gateway: Result<std::net::Ipv4Addr, confmap::ConfigError> = confmap::get_parsed("gateway");
 /// ```
-pub fn get_i32(key: &str) -> Option<i32> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        match value {
-            Value::Number(n) => n.as_i64().map(|n| n as i32),
-            _ => None,
-        }
-    } else {
-        None
-    }
+pub fn get_parsed<T>(key: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    Global.get_parsed(key)
 }
 
-/// this function will return Option<i16> when you put a key argument.
+/// this function will return the string value at a key as a variant of an enum, whatever its case.
 /// # Example
 /// ```
-/// confmap::get_int16("testGetInt16");
+/// #[derive(serde::Deserialize)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Mode {
+///     Fast,
+///     Safe,
+/// }
+///
+/// let mode = confmap::get_enum("mode").unwrap_or(Mode::Safe);
 /// ```
-pub fn get_i16(key: &str) -> Option<i16> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        match value {
-            Value::Number(n) => n.as_i64().map(|n| n as i16),
-            _ => None,
-        }
-    } else {
-        None
-    }
+pub fn get_enum<E: DeserializeOwned>(key: &str) -> Result<E, ConfigError> {
+    Global.get_enum(key)
 }
 
-/// this function will return Option<i8> when you put a key argument.
+/// this function will return the value at a key deserialized into any serde type, or the value of `default`.
 /// # Example
 /// ```
-/// confmap::get_int8("testGetInt8");
+/// let replicas: Vec<String> = confmap::get_or_else("replicas", Vec::new);
 /// ```
-pub fn get_int8(key: &str) -> Option<i8> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        match value {
-            Value::Number(n) => n.as_i64().map(|n| n as i8),
-            _ => None,
-        }
-    } else {
-        None
-    }
+pub fn get_or_else<T: DeserializeOwned>(key: &str, default: impl FnOnce() -> T) -> T {
+    Global.get_or_else(key, default)
 }
 
-/// this function will return Option<f64> when you put a key argument.
+/// this function will return the string at a key, or `default`.
 /// # Example
 /// ```
-/// confmap::get_float64("testGetFloat64");
+/// let host = confmap::get_string_or("server.host", "0.0.0.0");
 /// ```
-pub fn get_float64(key: &str) -> Option<f64> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        match value {
-            Value::Number(n) => n.as_f64(),
-            _ => None,
-        }
-    } else {
-        None
-    }
+pub fn get_string_or(key: &str, default: &str) -> String {
+    Global.get_string_or(key, default)
 }
 
-/// this function will return Option<Vec<f64>> when you put a key argument.
+/// this function will return the integer at a key, or `default`.
 /// # Example
 /// ```
-/// confmap::get_float64_array("testGetFloat64Array");
+/// let port = confmap::get_int64_or("server.port", 8080);
 /// ```
-pub fn get_float64_array(key: &str) -> Option<Vec<f64>> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        if let Value::Array(arr) = value {
-            let mut float64_array = Vec::new();
-            for element in arr {
-                if let Value::Number(n) = element {
-                    if let Some(int_value) = n.as_f64() {
-                        float64_array.push(int_value);
-                    }
-                }
-            }
-            Some(float64_array)
-        } else {
-            None
-        }
-    } else {
-        None
-    }
+pub fn get_int64_or(key: &str, default: i64) -> i64 {
+    Global.get_int64_or(key, default)
 }
 
-/// this function will return Option<f32> when you put a key argument.
+/// this function will return the number at a key, or `default`.
 /// # Example
 /// ```
-/// confmap::get_float32("testGetFloat32");
+/// let ratio = confmap::get_float64_or("sampling.ratio", 0.1);
 /// ```
-pub fn get_float32(key: &str) -> Option<f32> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        match value {
-            Value::Number(n) => n.as_f64().map(|n| n as f32),
-            _ => None,
-        }
-    } else {
-        None
-    }
+pub fn get_float64_or(key: &str, default: f64) -> f64 {
+    Global.get_float64_or(key, default)
 }
 
-/// this function will return Option<bool> when you put a key argument.
+/// this function will return the bool at a key, or `default`.
 /// # Example
 /// ```
-/// confmap::get_bool("testGetBool");
+/// let debug = confmap::get_bool_or("debug", false);
 /// ```
-pub fn get_bool(key: &str) -> Option<bool> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        value.as_bool()
-    } else {
-        None
-    }
+pub fn get_bool_or(key: &str, default: bool) -> bool {
+    Global.get_bool_or(key, default)
 }
 
-/// this function will return Option<serde_json::Value> when you put a key argument.
+/// this function will return Result<String, ConfigError> when you put a key argument, the error tells why there is no String.
 /// # Example
 /// ```
-/// confmap::get("testGet");
+/// confmap::try_get_string("testGetString");
 /// ```
-pub fn get(key: &str) -> Option<Value> {
-    CONFIGS.lock().unwrap().get(key).cloned()
+pub fn try_get_string(key: &str) -> Result<String, ConfigError> {
+    Global.try_get_string(key)
 }
 
-/// this function will return Option<Vec<serde_json::Value>> when you put a key argument.
+/// this function will return Result<i64, ConfigError> when you put a key argument, the error tells why there is no i64.
 /// # Example
 /// ```
-/// confmap::get_array("testGetArray");
+/// match confmap::try_get_int64("workers") {
+///     Ok(workers) => println!("{} workers", workers),
+///     Err(e) => println!("{}", e),
+/// }
 /// ```
-pub fn get_array(key: &str) -> Option<Vec<Value>> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(value) = configs.get(key) {
-        if let Value::Array(arr) = value {
-            let mut array = Vec::new();
-            for element in arr {
-                if let Value::Object(_) = element {
-                    array.push(element.clone());
-                }
-            }
-            Some(array)
-        } else {
-            None
-        }
-    } else {
-        None
-    }
+pub fn try_get_int64(key: &str) -> Result<i64, ConfigError> {
+    Global.try_get_int64(key)
 }
 
-/// this function will return Option<Map<String, Value>> when you put a key argument.
+/// this function will return Result<i32, ConfigError> when you put a key argument, the error tells why there is no i32.
 /// # Example
 /// ```
-/// confmap::get_map("testGetMap");
+/// confmap::try_get_i32("testGetI32");
 /// ```
-pub fn get_map(key: &str) -> Option<Map<String, Value>> {
-    let configs = CONFIGS.lock().unwrap();
-    if let Some(map) = configs.get(key) {
-        map.as_object().cloned()
-    } else {
-        None
-    }
+pub fn try_get_i32(key: &str) -> Result<i32, ConfigError> {
+    Global.try_get_i32(key)
 }
 
-#[cfg(test)]
-mod tests {
-    use std::env;
-    use std::io::Write;
-    use std::path::{PathBuf};
-    use super::*;
+/// this function will return Result<i16, ConfigError> when you put a key argument, the error tells why there is no i16.
+/// # Example
+/// ```
+/// confmap::try_get_i16("testGetI16");
+/// ```
+pub fn try_get_i16(key: &str) -> Result<i16, ConfigError> {
+    Global.try_get_i16(key)
+}
 
-    #[test]
-    fn it_works() {
-        let data = r#"
-        {
-            "testGetString": "YesMan",
-            "testGetInt64": 43,
-            "testGetStringArray": [
-                "+44 1234567",
-                "+44 2345678"
-            ]
-        }"#;
-        let path_buf = env::current_exe().expect("Failed to get executable path");
-        let path_str = path_buf
-            .ancestors()
-            .nth(4) // Adjust the number as needed to reach the desired parent directory
-            .expect("Invalid number of ancestors")
-            .to_str()
-            .expect("Failed to convert path to string");
-        let mut path = PathBuf::from(path_str);
-        path.push("config.json");
-        let mut file = std::fs::File::create(path.clone()).expect("create failed");
-        file.write_all(data.as_bytes()).expect("write failed");
-        add_config_path(path_str);
-        set_config_name("config.json");
-        read_config();
-        if path.as_path().exists() {
-            std::fs::remove_file(path.as_path()).expect("failed to delete test file");
-        }
-        assert_eq!(Some("YesMan".to_string()), get_string("testGetString"));
-        assert_eq!(Some(43), get_int64("testGetInt64"));
-        assert_eq!(Some(vec!["+44 1234567".to_string(), "+44 2345678".to_string()]), get_string_array("testGetStringArray"));
+/// this function will return Result<i8, ConfigError> when you put a key argument, the error tells why there is no i8.
+/// # Example
+/// ```
+/// confmap::try_get_i8("testGetI8");
+/// ```
+pub fn try_get_i8(key: &str) -> Result<i8, ConfigError> {
+    Global.try_get_i8(key)
+}
+
+/// this function will return Result<u64, ConfigError> when you put a key argument, the error tells why there is no u64.
+/// # Example
+/// ```
+/// confmap::try_get_u64("testGetU64");
+/// ```
+pub fn try_get_u64(key: &str) -> Result<u64, ConfigError> {
+    Global.try_get_u64(key)
+}
+
+/// this function will return Result<u32, ConfigError> when you put a key argument, the error tells why there is no u32.
+/// # Example
+/// ```
+/// confmap::try_get_u32("testGetU32");
+/// ```
+pub fn try_get_u32(key: &str) -> Result<u32, ConfigError> {
+    Global.try_get_u32(key)
+}
+
+/// this function will return Result<u16, ConfigError> when you put a key argument, the error tells why there is no u16.
+/// # Example
+/// ```
+/// confmap::try_get_u16("testGetU16");
+/// ```
+pub fn try_get_u16(key: &str) -> Result<u16, ConfigError> {
+    Global.try_get_u16(key)
+}
+
+/// this function will return Result<u8, ConfigError> when you put a key argument, the error tells why there is no u8.
+/// # Example
+/// ```
+/// confmap::try_get_u8("testGetU8");
+/// ```
+pub fn try_get_u8(key: &str) -> Result<u8, ConfigError> {
+    Global.try_get_u8(key)
+}
+
+/// this function will return Result<usize, ConfigError> when you put a key argument, the error tells why there is no usize.
+/// # Example
+/// ```
+/// confmap::try_get_usize("testGetUsize");
+/// ```
+pub fn try_get_usize(key: &str) -> Result<usize, ConfigError> {
+    Global.try_get_usize(key)
+}
+
+/// this function will return Result<f64, ConfigError> when you put a key argument, the error tells why there is no f64.
+/// # Example
+/// ```
+/// confmap::try_get_float64("testGetFloat64");
+/// ```
+pub fn try_get_float64(key: &str) -> Result<f64, ConfigError> {
+    Global.try_get_float64(key)
+}
+
+/// this function will return Result<bool, ConfigError> when you put a key argument, the error tells why there is no bool.
+/// # Example
+/// ```
+/// confmap::try_get_bool("testGetBool");
+/// ```
+pub fn try_get_bool(key: &str) -> Result<bool, ConfigError> {
+    Global.try_get_bool(key)
+}
+
+/// this function will return Option<String> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_string("testGetString");
+/// ```
+pub fn get_string(key: &str) -> Option<String> {
+    Global.get_string(key)
+}
+
+/// this function will return Option<char> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_char("csv.delimiter");
+/// ```
+pub fn get_char(key: &str) -> Option<char> {
+    Global.get_char(key)
+}
+
+/// this function will return Option<Vec<String>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_string_array("testGetStringArray");
+/// ```
+pub fn get_string_array(key: &str) -> Option<Vec<String>> {
+    Global.get_string_array(key)
+}
+
+/// this function will return Option<i64> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_int64("testGetInt64");
+/// ```
+pub fn get_int64(key: &str) -> Option<i64> {
+    Global.get_int64(key)
+}
+
+/// this function will return Option<Vec<i64>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_int64_array("testGetInt64Array");
+/// ```
+pub fn get_int64_array(key: &str) -> Option<Vec<i64>> {
+    Global.get_int64_array(key)
+}
+
+/// this function will return Option<Vec<i32>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_i32_array("testGetI32Array");
+/// ```
+pub fn get_i32_array(key: &str) -> Option<Vec<i32>> {
+    Global.get_i32_array(key)
+}
+
+/// this function will return Option<Vec<u64>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u64_array("testGetU64Array");
+/// ```
+pub fn get_u64_array(key: &str) -> Option<Vec<u64>> {
+    Global.get_u64_array(key)
+}
+
+/// this function will return Option<i64> when you put a key argument, the same as get_int64.
+/// # Example
+/// ```
+/// confmap::get_i64("testGetI64");
+/// ```
+pub fn get_i64(key: &str) -> Option<i64> {
+    Global.get_i64(key)
+}
+
+/// this function will return Option<i32> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_i32("testGetInt32");
+/// ```
+pub fn get_i32(key: &str) -> Option<i32> {
+    Global.get_i32(key)
+}
+
+/// this function will return Option<i16> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_i16("testGetInt16");
+/// ```
+pub fn get_i16(key: &str) -> Option<i16> {
+    Global.get_i16(key)
+}
+
+/// this function will return Option<i8> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_i8("testGetI8");
+/// ```
+pub fn get_i8(key: &str) -> Option<i8> {
+    Global.get_i8(key)
+}
+
+/// this function will return Option<i8> when you put a key argument.
+#[deprecated(note = "use get_i8, named like the other sized getters")]
+pub fn get_int8(key: &str) -> Option<i8> {
+    Global.get_i8(key)
+}
+
+/// this function will return Option<u64> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u64("testGetU64");
+/// ```
+pub fn get_u64(key: &str) -> Option<u64> {
+    Global.get_u64(key)
+}
+
+/// this function will return Option<u32> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u32("testGetU32");
+/// ```
+pub fn get_u32(key: &str) -> Option<u32> {
+    Global.get_u32(key)
+}
+
+/// this function will return Option<u16> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u16("testGetU16");
+/// ```
+pub fn get_u16(key: &str) -> Option<u16> {
+    Global.get_u16(key)
+}
+
+/// this function will return Option<u8> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u8("testGetU8");
+/// ```
+pub fn get_u8(key: &str) -> Option<u8> {
+    Global.get_u8(key)
+}
+
+/// this function will return Option<usize> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_usize("testGetUsize");
+/// ```
+pub fn get_usize(key: &str) -> Option<usize> {
+    Global.get_usize(key)
+}
+
+/// this function will return Option<NonZeroU32> when you put a key argument.
+/// # Example
+/// ```
+/// let workers = confmap::get_nonzero_u32("workers").map_or(4, |workers| workers.get());
+/// ```
+pub fn get_nonzero_u32(key: &str) -> Option<NonZeroU32> {
+    Global.get_nonzero_u32(key)
+}
+
+/// this function will return Option<NonZeroU64> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_nonzero_u64("testGetNonzeroU64");
+/// ```
+pub fn get_nonzero_u64(key: &str) -> Option<NonZeroU64> {
+    Global.get_nonzero_u64(key)
+}
+
+/// this function will return the two numbers of a value such as `"8000-9000"` or `[8000, 9000]` when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u64_pair("retry.backoffMillis");
+/// ```
+pub fn get_u64_pair(key: &str) -> Option<(u64, u64)> {
+    Global.get_u64_pair(key)
+}
+
+/// this function will return the range, bounds included, of a value such as `"8000-9000"` or `[8000, 9000]` when you put a key argument.
+/// # Example
+/// ```
+/// for port in confmap::get_range("server.ports").unwrap_or(8000..=8000) {
+///     println!("{}", port);
+/// }
+/// ```
+pub fn get_range(key: &str) -> Option<RangeInclusive<u64>> {
+    Global.get_range(key)
+}
+
+/// this function will return Option<i128> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_i128("testGetI128");
+/// ```
+pub fn get_i128(key: &str) -> Option<i128> {
+    Global.get_i128(key)
+}
+
+/// this function will return Option<u128> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_u128("testGetU128");
+/// ```
+pub fn get_u128(key: &str) -> Option<u128> {
+    Global.get_u128(key)
+}
+
+/// this function will return Option<rust_decimal::Decimal> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_decimal("testGetDecimal");
+/// ```
+#[cfg(feature = "rust_decimal")]
+pub fn get_decimal(key: &str) -> Option<rust_decimal::Decimal> {
+    Global.get_decimal(key)
+}
+
+/// this function will return Option<chrono::DateTime<Utc>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_datetime("certificate.expiresAt");
+/// ```
+#[cfg(feature = "chrono")]
+pub fn get_datetime(key: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    Global.get_datetime(key)
+}
+
+/// this function will return Option<uuid::Uuid> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_uuid("tenantId");
+/// ```
+#[cfg(feature = "uuid")]
+pub fn get_uuid(key: &str) -> Option<uuid::Uuid> {
+    Global.get_uuid(key)
+}
+
+/// this function will return Option<Vec<uuid::Uuid>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_uuid_array("clusterIds");
+/// ```
+#[cfg(feature = "uuid")]
+pub fn get_uuid_array(key: &str) -> Option<Vec<uuid::Uuid>> {
+    Global.get_uuid_array(key)
+}
+
+/// this function will return Option<log::LevelFilter> when you put a key argument.
+/// # Example
+/// ```
+/// if let Some(level) = confmap::get_log_level("logging.level") {
+///     log::set_max_level(level);
+/// }
+/// ```
+#[cfg(feature = "log")]
+pub fn get_log_level(key: &str) -> Option<log::LevelFilter> {
+    Global.get_log_level(key)
+}
+
+/// this function will return Option<f64> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_float64("testGetFloat64");
+/// ```
+pub fn get_float64(key: &str) -> Option<f64> {
+    Global.get_float64(key)
+}
+
+/// this function will return Option<Vec<f64>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_float64_array("testGetFloat64Array");
+/// ```
+pub fn get_float64_array(key: &str) -> Option<Vec<f64>> {
+    Global.get_float64_array(key)
+}
+
+/// this function will return Option<Vec<f32>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_f32_array("testGetF32Array");
+/// ```
+pub fn get_f32_array(key: &str) -> Option<Vec<f32>> {
+    Global.get_f32_array(key)
+}
+
+/// this function will return Option<f32> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_float32("testGetFloat32");
+/// ```
+pub fn get_float32(key: &str) -> Option<f32> {
+    Global.get_float32(key)
+}
+
+/// this function will return Option<bool> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_bool("testGetBool");
+/// ```
+pub fn get_bool(key: &str) -> Option<bool> {
+    Global.get_bool(key)
+}
+
+/// this function will return Option<Vec<bool>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_bool_array("testGetBoolArray");
+/// ```
+pub fn get_bool_array(key: &str) -> Option<Vec<bool>> {
+    Global.get_bool_array(key)
+}
+
+/// this function will return Option<serde_json::Value> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get("testGet");
+/// ```
+pub fn get(key: &str) -> Option<Value> {
+    Global.get(key)
+}
+
+/// this function will return Option<Vec<serde_json::Value>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_array("testGetArray");
+/// ```
+pub fn get_array(key: &str) -> Option<Vec<Value>> {
+    Global.get_array(key)
+}
+
+/// this function will return every element of the array at a key, whatever its type.
+/// # Example
+/// ```
+/// confmap::get_value_array("testGetValueArray");
+/// ```
+pub fn get_value_array(key: &str) -> Option<Vec<Value>> {
+    Global.get_value_array(key)
+}
+
+/// this function will return Option<Vec<Map<String, Value>>> when you put a key argument.
+/// # Example
+/// ```
+/// for server in confmap::get_map_array("servers").unwrap_or_default() {
+///     println!("{:?}", server.get("host"));
+/// }
+/// ```
+pub fn get_map_array(key: &str) -> Option<Vec<Map<String, Value>>> {
+    Global.get_map_array(key)
+}
+
+/// this function will return Option<ConfigSection> when you put a key argument.
+/// # Example
+/// ```
+/// use confmap::ConfigRead;
+///
+/// if let Some(database) = confmap::get_map("database") {
+///     database.get_string("host");
+///     database.get_int64("port");
+/// }
+/// ```
+pub fn get_map(key: &str) -> Option<ConfigSection> {
+    Global.get_map(key)
+}
+
+/// this function will return Option<HashMap<String, String>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_string_map("labels");
+/// ```
+pub fn get_string_map(key: &str) -> Option<HashMap<String, String>> {
+    Global.get_string_map(key)
+}
+
+/// this function will return Option<HashMap<String, i64>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_int64_map("quotas");
+/// ```
+pub fn get_int64_map(key: &str) -> Option<HashMap<String, i64>> {
+    Global.get_int64_map(key)
+}
+
+/// this function will return the object at a key as a Config, so a component can be given only its own section.
+/// # Example
+/// ```
+/// use confmap::ConfigRead;
+///
+/// if let Some(database) = confmap::sub("database") {
+///     database.get_string("host");
+/// }
+/// ```
+pub fn sub(key: &str) -> Option<Config> {
+    Global.sub(key)
+}
+
+/// this function will return the number of bytes of a size, such as `"10MB"`, `"512KiB"` or `"1.5G"`, when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_byte_size("cache.maxSize");
+/// ```
+pub fn get_byte_size(key: &str) -> Option<u64> {
+    Global.get_byte_size(key)
+}
+
+/// this function will return Option<Vec<u8>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_bytes("webhook.hmacSecret");
+/// ```
+pub fn get_bytes(key: &str) -> Option<Vec<u8>> {
+    Global.get_bytes(key)
+}
+
+/// this function will return Option<SocketAddr> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_socket_addr("server.listen");
+/// ```
+pub fn get_socket_addr(key: &str) -> Option<SocketAddr> {
+    Global.get_socket_addr(key)
+}
+
+/// this function will return Result<SocketAddr, ConfigError> when you put a key argument, the error tells why there is no socket address, e.g. a malformed one.
+/// # Example
+/// ```
+/// if let Err(e) = confmap::try_get_socket_addr("server.listen") {
+///     eprintln!("{}", e);
+/// }
+/// ```
+pub fn try_get_socket_addr(key: &str) -> Result<SocketAddr, ConfigError> {
+    Global.try_get_socket_addr(key)
+}
+
+/// this function will return Option<Vec<SocketAddr>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_socket_addr_array("server.listen");
+/// ```
+pub fn get_socket_addr_array(key: &str) -> Option<Vec<SocketAddr>> {
+    Global.get_socket_addr_array(key)
+}
+
+/// this function will return Result<Vec<SocketAddr>, ConfigError> when you put a key argument, the error tells why there is no socket addresses, e.g. a malformed one.
+/// # Example
+/// ```
+/// if let Err(e) = confmap::try_get_socket_addr_array("server.listen") {
+///     eprintln!("{}", e);
+/// }
+/// ```
+pub fn try_get_socket_addr_array(key: &str) -> Result<Vec<SocketAddr>, ConfigError> {
+    Global.try_get_socket_addr_array(key)
+}
+
+/// this function will return Option<IpAddr> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_ip_addr("server.bind");
+/// ```
+pub fn get_ip_addr(key: &str) -> Option<IpAddr> {
+    Global.get_ip_addr(key)
+}
+
+/// this function will return Result<IpAddr, ConfigError> when you put a key argument, the error tells why there is no ip address, e.g. a malformed one.
+/// # Example
+/// ```
+/// if let Err(e) = confmap::try_get_ip_addr("server.bind") {
+///     eprintln!("{}", e);
+/// }
+/// ```
+pub fn try_get_ip_addr(key: &str) -> Result<IpAddr, ConfigError> {
+    Global.try_get_ip_addr(key)
+}
+
+/// this function will return Option<Vec<IpAddr>> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_ip_addr_array("server.allowed");
+/// ```
+pub fn get_ip_addr_array(key: &str) -> Option<Vec<IpAddr>> {
+    Global.get_ip_addr_array(key)
+}
+
+/// this function will return Result<Vec<IpAddr>, ConfigError> when you put a key argument, the error tells why there is no ip addresses, e.g. a malformed one.
+/// # Example
+/// ```
+/// if let Err(e) = confmap::try_get_ip_addr_array("server.allowed") {
+///     eprintln!("{}", e);
+/// }
+/// ```
+pub fn try_get_ip_addr_array(key: &str) -> Result<Vec<IpAddr>, ConfigError> {
+    Global.try_get_ip_addr_array(key)
+}
+
+/// this function will return Option<Rate> when you put a key argument.
+/// # Example
+/// ```
+/// confmap::get_rate("testGetRate");
+/// ```
+pub fn get_rate(key: &str) -> Option<Rate> {
+    Global.get_rate(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::Write;
+    use std::path::{PathBuf};
+    use std::sync::Arc;
+    use super::*;
+
+    /// serializes the tests using the process-wide configuration.
+    static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+    pub(crate) fn lock_global_state() -> std::sync::MutexGuard<'static, ()> {
+        GLOBAL_STATE.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn it_works() {
+        let _guard = lock_global_state();
+        let data = r#"
+        {
+            "testGetString": "YesMan",
+            "testGetInt64": 43,
+            "testGetStringArray": [
+                "+44 1234567",
+                "+44 2345678"
+            ]
+        }"#;
+        let path_buf = env::current_exe().expect("Failed to get executable path");
+        let path_str = path_buf
+            .ancestors()
+            .nth(4) // Adjust the number as needed to reach the desired parent directory
+            .expect("Invalid number of ancestors")
+            .to_str()
+            .expect("Failed to convert path to string");
+        let mut path = PathBuf::from(path_str);
+        path.push("config.json");
+        let mut file = std::fs::File::create(path.clone()).expect("create failed");
+        file.write_all(data.as_bytes()).expect("write failed");
+        add_config_path(path_str);
+        set_config_name("config.json");
+        read_config();
+        if path.as_path().exists() {
+            std::fs::remove_file(path.as_path()).expect("failed to delete test file");
+        }
+        assert_eq!(Some("YesMan".to_string()), get_string("testGetString"));
+        assert_eq!(Some(43), get_int64("testGetInt64"));
+        assert_eq!(Some(vec!["+44 1234567".to_string(), "+44 2345678".to_string()]), get_string_array("testGetStringArray"));
+    }
+
+    #[test]
+    fn scope_for_subcommand_falls_back_to_global() {
+        let _guard = lock_global_state();
+        let data: Map<String, Value> = serde_json::from_str(r#"
+        {
+            "scopeTestLevel": "info",
+            "scopeTestPort": 80,
+            "commands": {
+                "scopeTestServe": { "scopeTestPort": 8080 }
+            }
+        }"#).unwrap();
+        CONFIGS.rcu(|configs| {
+            let mut configs = Map::clone(configs);
+            configs.extend(data.clone());
+            configs
+        });
+        let serve = scope_for_subcommand("scopeTestServe");
+        assert_eq!(Some(8080), serve.get_int64("scopeTestPort"));
+        assert_eq!(Some("info".to_string()), serve.get_string("scopeTestLevel"));
+        assert_eq!(Some(80), scope_for_subcommand("scopeTestOther").get_int64("scopeTestPort"));
+    }
+
+    #[test]
+    fn missing_config_files_are_watched() {
+        let _guard = lock_global_state();
+        let file = env::temp_dir().join("confmap_missing_watched_test.json");
+        add_config_file(file.to_str().unwrap());
+        let files = config_files();
+        CONFIG_FILES.lock().unwrap().retain(|location| !matches!(location, ConfigLocation::File(added) if *added == file));
+        assert!(files.contains(&file));
+    }
+
+    #[test]
+    fn workspace_config_files_are_ordered_from_root() {
+        let root = env::temp_dir().join(format!("confmap_workspace_{}", std::process::id()));
+        let package = root.join("crates").join("server");
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("tool.config.json"), "{}").unwrap();
+        std::fs::write(package.join("tool.config.json"), "{}").unwrap();
+        let files = workspace_config_files(&package, "tool.config.json");
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(vec![root.join("tool.config.json"), package.join("tool.config.json")], files);
+    }
+
+    /// a source of the tests loading one document, then another once switched. a None document fails to load,
+    /// like a file being written.
+    struct SwitchedSource {
+        name: &'static str,
+        layer: Layer,
+        before: &'static str,
+        after: Option<&'static str>,
+        switched: Arc<AtomicBool>,
+    }
+
+    impl Source for SwitchedSource {
+        fn load(&self) -> Result<Map<String, Value>, SourceError> {
+            let document = if self.switched.load(Ordering::SeqCst) { self.after } else { Some(self.before) };
+            let document = document.ok_or_else(|| SourceError::Parse("unexpected end of file".to_string()))?;
+            Ok(serde_json::from_str(document)?)
+        }
+
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn layer(&self) -> Layer {
+            self.layer
+        }
+    }
+
+    /// a SwitchedSource added to the process-wide sources, removed again when dropped.
+    struct AddedSource {
+        name: &'static str,
+        switched: Arc<AtomicBool>,
+    }
+
+    impl AddedSource {
+        fn switch(&self) {
+            self.switched.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl Drop for AddedSource {
+        fn drop(&mut self) {
+            source::SOURCES.lock().unwrap_or_else(|e| e.into_inner()).retain(|source| source.name() != self.name);
+        }
+    }
+
+    fn add_switched_source(name: &'static str, layer: Layer, before: &'static str, after: Option<&'static str>) -> AddedSource {
+        let switched = Arc::new(AtomicBool::new(false));
+        add_source(Box::new(SwitchedSource { name, layer, before, after, switched: switched.clone() }));
+        AddedSource { name, switched }
+    }
+
+    #[test]
+    fn file_urls_are_config_files_wherever_they_are_added() {
+        let _guard = lock_global_state();
+        let folder = config_path();
+        add_config_path("file:///etc/confmap_url_test/config.json");
+        let added = source::SOURCES.lock().unwrap().iter().any(|source| source.name() == "/etc/confmap_url_test/config.json");
+        source::SOURCES.lock().unwrap().retain(|source| source.name() != "/etc/confmap_url_test/config.json");
+        assert!(added);
+        assert_eq!(folder, config_path());
+    }
+
+    #[test]
+    fn read_config_merges_added_sources() {
+        let _guard = lock_global_state();
+        let _first = add_switched_source("first source test", Layer::Custom, r#"{"sourceTestHost": "a", "sourceTestPort": 1}"#, None);
+        let _second = add_switched_source("second source test", Layer::Custom, r#"{"sourceTestHost": "b"}"#, None);
+        let mut configs = Loaded::default();
+        read_sources(&mut configs, MergeStrategy::Replace);
+        assert_eq!(Some(&Value::from("b")), configs.values().get("sourceTestHost"));
+        assert_eq!(Some(&Value::from(1)), configs.values().get("sourceTestPort"));
+    }
+
+    #[test]
+    fn added_config_files_are_deep_merged_in_order() {
+        let dir = env::temp_dir().join(format!("confmap_layers_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let defaults = dir.join("defaults.json");
+        let overrides = dir.join("overrides.json");
+        std::fs::write(&defaults, r#"{"layerTestDb": {"host": "localhost", "port": 5432}}"#).unwrap();
+        std::fs::write(&overrides, r#"{"layerTestDb": {"host": "db.prod"}}"#).unwrap();
+        add_config_file(&defaults.to_string_lossy());
+        add_config_file(&overrides.to_string_lossy());
+        let mut configs = Loaded::default();
+        read_config_files(&mut configs, MergeStrategy::DeepMerge);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"host": "db.prod", "port": 5432}"#).unwrap();
+        assert_eq!(Some(&expected), configs.values().get("layerTestDb"));
+        assert_eq!(Some(&overrides.display().to_string()), configs.resolve().1.get("layerTestDb.host"));
+    }
+
+    #[test]
+    fn summary_lists_important_keys_with_sources() {
+        let _guard = lock_global_state();
+        let mut loaded = Loaded::current();
+        let values = serde_json::from_str(r#"{"summaryTest": {"port": 8080, "password": "hunter2"}}"#).unwrap();
+        loaded.push(Layer::File, "summary.json", values, MergeStrategy::DeepMerge);
+        loaded.publish().unwrap();
+        set_key_metadata("summaryTest.port", KeyMetadata::new().important());
+        set_key_metadata("summaryTest.password", KeyMetadata::new().important());
+        mark_secret("summaryTest.password");
+        let summary = summary();
+        assert!(summary.contains("summaryTest.port      8080    summary.json"));
+        assert!(summary.contains("summaryTest.password  ******  summary.json"));
+        assert!(!summary.contains("hunter2"));
+    }
+
+    struct Fixed(Map<String, Value>);
+
+    impl ConfigRead for Fixed {
+        fn get(&self, key: &str) -> Option<Value> {
+            self.0.get(key).cloned()
+        }
+    }
+
+    #[test]
+    fn is_set_tells_null_from_missing() {
+        let fixed = Fixed(serde_json::from_str(r#"{"x": null, "y": false, "z": ""}"#).unwrap());
+        assert!(fixed.is_set("x") && fixed.is_set("y") && fixed.is_set("z"));
+        assert!(!fixed.is_set("w"));
+        assert_eq!(Some(Value::Bool(false)), fixed.get_first(&["w", "y", "z"]));
+        assert_eq!(None, fixed.get_first(&["v", "w"]));
+        assert_eq!(Some(None), fixed.get_optional("x"));
+        assert_eq!(Some(Some(Value::Bool(false))), fixed.get_optional("y"));
+        assert_eq!(None, fixed.get_optional("w"));
+    }
+
+    #[test]
+    fn signed_getters_reject_out_of_range_numbers() {
+        let fixed = Fixed(serde_json::from_str(r#"{"small": -100, "big": 999999}"#).unwrap());
+        assert_eq!(Some(-100), fixed.get_i8("small"));
+        assert_eq!(None, fixed.get_i16("big"));
+        assert_eq!(Some(999999), fixed.get_i32("big"));
+    }
+
+    #[test]
+    fn chars_and_nonzero_numbers() {
+        let fixed = Fixed(serde_json::from_str(r#"{"delimiter": ";", "word": "ab", "workers": 8, "zero": 0}"#).unwrap());
+        assert_eq!(Some(';'), fixed.get_char("delimiter"));
+        assert_eq!(None, fixed.get_char("word"));
+        assert_eq!(NonZeroU32::new(8), fixed.get_nonzero_u32("workers"));
+        assert_eq!(None, fixed.get_nonzero_u64("zero"));
+    }
+
+    #[test]
+    fn unsigned_getters_reject_out_of_range_numbers() {
+        let fixed = Fixed(serde_json::from_str(r#"{"port": 8080, "big": 70000, "negative": -1}"#).unwrap());
+        assert_eq!(Some(8080), fixed.get_u16("port"));
+        assert_eq!(None, fixed.get_u16("big"));
+        assert_eq!(Some(70000), fixed.get_usize("big"));
+        assert_eq!(None, fixed.get_u64("negative"));
+        assert_eq!(None, fixed.get_u8("negative"));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn datetimes_are_parsed() {
+        let fixed = Fixed(serde_json::from_str(r#"{"at": "2025-03-01T13:00:00+01:00", "epoch": 1740830400, "half": 1740830400.5, "bad": "tomorrow"}"#).unwrap());
+        let expected = chrono::DateTime::from_timestamp(1740830400, 0).unwrap();
+        assert_eq!(Some(expected), fixed.get_datetime("at"));
+        assert_eq!(Some(expected), fixed.get_datetime("epoch"));
+        assert_eq!(Some(expected + chrono::Duration::milliseconds(500)), fixed.get_datetime("half"));
+        assert_eq!(None, fixed.get_datetime("bad"));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids_are_parsed() {
+        let _guard = lock_global_state();
+        let fixed = Fixed(serde_json::from_str(r#"{"id": "67e55044-10b1-426f-9247-bb680e5fe0c8", "ids": ["67e55044-10b1-426f-9247-bb680e5fe0c8", "x"]}"#).unwrap());
+        let id = uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(Some(id), fixed.get_uuid("id"));
+        assert_eq!(Some(vec![id]), fixed.get_uuid_array("ids"));
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn log_levels_ignore_case() {
+        let fixed = Fixed(serde_json::from_str(r#"{"a": "debug", "b": "INFO", "c": "loud"}"#).unwrap());
+        assert_eq!(Some(log::LevelFilter::Debug), fixed.get_log_level("a"));
+        assert_eq!(Some(log::LevelFilter::Info), fixed.get_log_level("b"));
+        assert_eq!(None, fixed.get_log_level("c"));
+    }
+
+    #[test]
+    fn values_are_deserialized() {
+        let fixed = Fixed(serde_json::from_str(r#"{"limits": {"rps": 50}, "ports": [80, 443], "name": 1}"#).unwrap());
+        let limits: std::collections::HashMap<String, u32> = fixed.get_as("limits").unwrap();
+        assert_eq!(Some(&50), limits.get("rps"));
+        assert_eq!(Ok(vec![80u16, 443]), fixed.get_as("ports"));
+        assert_eq!(Ok(None::<u16>), fixed.get_as("missing"));
+        assert_eq!(Err(ConfigError::KeyMissing("missing".to_string())), fixed.get_as::<u16>("missing"));
+        assert!(matches!(fixed.get_as::<String>("name"), Err(ConfigError::Deserialize { .. })));
+    }
+
+    #[test]
+    fn required_keys_tell_what_is_wrong() {
+        let _guard = lock_global_state();
+        let _port = override_for_scope("requireTest.port", "eighty").unwrap();
+        let _name = override_for_scope("requireTest.name", 7).unwrap();
+        assert_eq!(ConfigError::KeyMissing("requireTest.missing".to_string()), require::<u16>("requireTest.missing").unwrap_err().error);
+        assert_eq!(ConfigError::KeyMissing("requireTest.missing".to_string()), require_string("requireTest.missing").unwrap_err().error);
+        let mistyped = require::<u16>("requireTest.port").unwrap_err();
+        assert_eq!("u16", mistyped.expected);
+        assert!(matches!(&mistyped.error, ConfigError::Deserialize { key, message } if key == "requireTest.port" && message.contains("expected u16")));
+        assert_eq!(
+            ConfigError::WrongType { key: "requireTest.name".to_string(), expected: "string", found: "number" },
+            require_string("requireTest.name").unwrap_err().error
+        );
+        assert!(require_string("requireTest.name").unwrap_err().to_string().starts_with("required key requireTest.name (string): requireTest.name is a number, expected a string, "));
+    }
+
+    #[test]
+    fn values_are_parsed_from_their_text() {
+        let fixed = Fixed(serde_json::from_str(r#"{"level": "3", "count": 3, "bad": "three", "list": [3]}"#).unwrap());
+        assert_eq!(Ok(3u8), fixed.get_parsed("level"));
+        assert_eq!(Ok(3u8), fixed.get_parsed("count"));
+        assert!(matches!(fixed.get_parsed::<u8>("bad"), Err(ConfigError::Malformed { .. })));
+        assert_eq!(Err(ConfigError::WrongType { key: "list".to_string(), expected: "string", found: "array" }), fixed.get_parsed::<u8>("list"));
+        assert_eq!(Err(ConfigError::KeyMissing("none".to_string())), fixed.get_parsed::<u8>("none"));
+    }
+
+    #[test]
+    fn coercion_converts_strings_numbers_and_bools() {
+        let values: Map<String, Value> = serde_json::from_str(r#"{"port": "8080", "debug": "on", "ratio": "0.5", "count": 3, "flags": ["1", 2]}"#).unwrap();
+        assert_eq!(None, Fixed(values.clone()).get_int64("port"));
+        let config = Config::with_options(values, ReadOptions { coercion: true, ..ReadOptions::default() });
+        assert_eq!(Some(8080), config.get_u16("port"));
+        assert_eq!(Some(true), config.get_bool("debug"));
+        assert_eq!(Some(0.5), config.get_float64("ratio"));
+        assert_eq!(Some("3".to_string()), config.get_string("count"));
+        assert_eq!(Some(vec![1, 2]), config.get_int64_array("flags"));
+    }
+
+    #[test]
+    fn sections_normalize_keys_like_the_configuration() {
+        let _guard = lock_global_state();
+        set_case_insensitive(true).unwrap();
+        let tls = override_for_scope("sectionKeysTest.Enable-TLS", true).unwrap();
+        let read = [
+            get_map("sectionKeysTest").and_then(|section| section.get_bool("Enable-TLS")),
+            sub("SectionKeysTest").and_then(|config| config.get_bool("ENABLE-TLS")),
+        ];
+        drop(tls);
+        set_case_insensitive(false).unwrap();
+        assert_eq!([Some(true); 2], read);
+    }
+
+    #[test]
+    fn the_process_wide_conversions_only_apply_to_its_views() {
+        let _guard = lock_global_state();
+        let _port = override_for_scope("coercionTest.port", "8080").unwrap();
+        set_coercion(true).unwrap();
+        let converted = [
+            get_int64("coercionTest.port"),
+            snapshot().get_int64("coercionTest.port"),
+            get_map("coercionTest").and_then(|section| section.get_int64("port")),
+            sub("coercionTest").and_then(|config| config.get_int64("port")),
+        ];
+        let standalone = Config::from_value(serde_json::json!({"port": "8080"})).unwrap().get_int64("port");
+        set_coercion(false).unwrap();
+        assert_eq!([Some(8080); 4], converted);
+        assert_eq!(None, standalone);
+        assert_eq!(None, get_int64("coercionTest.port"));
+    }
+
+    #[test]
+    fn mixed_arrays_are_skipped_or_rejected() {
+        let values: Map<String, Value> = serde_json::from_str(r#"{"flags": [true, "x", false], "ids": [1, 3000000000, -2]}"#).unwrap();
+        let fixed = Fixed(values.clone());
+        assert_eq!(Some(vec![true, false]), fixed.get_bool_array("flags"));
+        assert_eq!(Some(vec![1, -2]), fixed.get_i32_array("ids"));
+        assert_eq!(Some(vec![1, 3000000000]), fixed.get_u64_array("ids"));
+        let rejecting = Config::with_options(values, ReadOptions { mixed_arrays: MixedArrays::Reject, ..ReadOptions::default() });
+        assert_eq!(None, rejecting.get_bool_array("flags"));
+        assert_eq!(Some(vec![1, 3000000000, -2]), rejecting.get_int64_array("ids"));
+    }
+
+    #[test]
+    fn object_elements_are_returned() {
+        let values: Map<String, Value> = serde_json::from_str(r#"{"servers": [{"host": "a"}, "b", {"host": "c"}]}"#).unwrap();
+        let fixed = Fixed(values.clone());
+        let servers = fixed.get_map_array("servers").unwrap();
+        assert_eq!(vec![Some(&Value::from("a")), Some(&Value::from("c"))], servers.iter().map(|server| server.get("host")).collect::<Vec<_>>());
+        assert_eq!(2, fixed.get_array("servers").unwrap().len());
+        assert_eq!(3, fixed.get_value_array("servers").unwrap().len());
+        let rejecting = Config::with_options(values, ReadOptions { mixed_arrays: MixedArrays::Reject, ..ReadOptions::default() });
+        assert_eq!(None, rejecting.get_array("servers"));
+    }
+
+    #[test]
+    fn map_values_are_converted() {
+        let fixed = Fixed(serde_json::from_str(r#"{"labels": {"team": "core", "tier": 1}}"#).unwrap());
+        let labels = fixed.get_string_map("labels").unwrap();
+        assert_eq!(1, labels.len());
+        assert_eq!(Some(&"core".to_string()), labels.get("team"));
+        assert_eq!(Some(1), fixed.get_int64_map("labels").and_then(|quotas| quotas.get("tier").copied()));
+    }
+
+    #[test]
+    fn enum_variants_ignore_case() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Mode {
+            Fast,
+            Safe,
+        }
+        let fixed = Fixed(serde_json::from_str(r#"{"a": "FAST", "b": "Safe", "c": "slow"}"#).unwrap());
+        assert_eq!(Ok(Mode::Fast), fixed.get_enum("a"));
+        assert_eq!(Ok(Mode::Safe), fixed.get_enum("b"));
+        assert_eq!(
+            Err(ConfigError::Malformed { key: "c".to_string(), value: "slow".to_string(), message: "not one of fast, safe".to_string() }),
+            fixed.get_enum::<Mode>("c")
+        );
+        assert_eq!(r#"c is "slow", not one of fast, safe"#, fixed.get_enum::<Mode>("c").unwrap_err().to_string());
+    }
+
+    #[test]
+    fn ranges_are_parsed() {
+        let fixed = Fixed(serde_json::from_str(r#"{"ports": "8000-9000", "window": [5, 10], "backwards": "9-1", "bad": [1, 2, 3]}"#).unwrap());
+        assert_eq!(Some(8000..=9000), fixed.get_range("ports"));
+        assert_eq!(Some((5, 10)), fixed.get_u64_pair("window"));
+        assert_eq!(Some((9, 1)), fixed.get_u64_pair("backwards"));
+        assert_eq!(None, fixed.get_range("backwards"));
+        assert_eq!(None, fixed.get_range("bad"));
+    }
+
+    #[test]
+    fn try_getters_tell_why_a_value_is_missing() {
+        let _guard = lock_global_state();
+        let fixed = Fixed(serde_json::from_str(r#"{"port": 70000, "name": "api", "negative": -1, "ratio": 0.5}"#).unwrap());
+        assert_eq!(Ok(70000), fixed.try_get_int64("port"));
+        assert_eq!(Err(ConfigError::KeyMissing("host".to_string())), fixed.try_get_string("host"));
+        assert_eq!(Err(ConfigError::WrongType { key: "name".to_string(), expected: "integer", found: "string" }), fixed.try_get_u32("name"));
+        assert_eq!(Err(ConfigError::OutOfRange { key: "port".to_string(), expected: "u16", value: "70000".to_string() }), fixed.try_get_u16("port"));
+        assert_eq!(Err(ConfigError::OutOfRange { key: "negative".to_string(), expected: "u64", value: "-1".to_string() }), fixed.try_get_u64("negative"));
+        assert_eq!(Err(ConfigError::WrongType { key: "ratio".to_string(), expected: "integer", found: "number" }), fixed.try_get_i32("ratio"));
+    }
+
+    #[test]
+    fn defaults_are_given_for_missing_keys() {
+        let fixed = Fixed(serde_json::from_str(r#"{"host": "db", "replicas": ["a"]}"#).unwrap());
+        assert_eq!("db", fixed.get_string_or("host", "localhost"));
+        assert_eq!(8080, fixed.get_int64_or("port", 8080));
+        assert_eq!(vec!["a".to_string()], fixed.get_or_else("replicas", Vec::<String>::new));
+        assert_eq!(3, fixed.get_or_else("retries", || 3u8));
+    }
+
+    #[test]
+    fn defaults_stay_below_the_files() {
+        let _guard = lock_global_state();
+        set_default("defaultsTest.port", 8080).unwrap();
+        set_defaults_from(serde_json::json!({"defaultsTest": {"host": "localhost"}})).unwrap();
+        merge_value("defaultsTest.host", Value::from("db")).unwrap();
+        assert_eq!(Some(8080), get_int64("defaultsTest.port"));
+        assert_eq!(Some("db".to_string()), get_string("defaultsTest.host"));
+
+        #[derive(serde::Serialize)]
+        struct Settings {
+            #[serde(rename = "defaultsTestStruct")]
+            section: Section,
+        }
+        #[derive(serde::Serialize)]
+        struct Section {
+            workers: u32,
+            tls: bool,
+        }
+        set_defaults_from_struct(&Settings { section: Section { workers: 4, tls: true } }).unwrap();
+        assert_eq!(Some(4), get_u32("defaultsTestStruct.workers"));
+        assert_eq!(Some(true), get_bool("defaultsTestStruct.tls"));
+    }
+
+    #[test]
+    fn set_and_removed_keys() {
+        let _guard = lock_global_state();
+        merge_value("setTest", serde_json::json!({"host": "a", "port": 1})).unwrap();
+        set("setTest.port", 2).unwrap();
+        assert_eq!(Some(2), get_int64("setTest.port"));
+        remove("setTest").unwrap();
+        assert!(!is_set("setTest.host") && !is_set("setTest.port"));
+        set("setTest.host", "b").unwrap();
+        assert_eq!(Some("b".to_string()), get_string("setTest.host"));
+        assert!(!is_set("setTest.port"));
+    }
+
+    #[test]
+    fn reset_clears_the_loaded_configuration() {
+        let _guard = lock_global_state();
+        set_config_name("resetTest.json");
+        set_default("resetTest.port", 1).unwrap();
+        set("resetTest.host", "h").unwrap();
+        reset().unwrap();
+        assert!(!is_set("resetTest"));
+        assert_eq!("", config_name());
+        read_config();
+        assert!(!is_set("resetTest"));
+    }
+
+    #[test]
+    fn scoped_overrides_are_restored() {
+        let _guard = lock_global_state();
+        set("scopedTest.level", "info").unwrap();
+        {
+            let _level = override_for_scope("scopedTest.level", "debug").unwrap();
+            let _fresh = override_for_scope("scopedTest.fresh", 1).unwrap();
+            assert_eq!(Some("debug".to_string()), get_string("scopedTest.level"));
+            assert_eq!(Some(1), get_int64("scopedTest.fresh"));
+        }
+        assert_eq!(Some("info".to_string()), get_string("scopedTest.level"));
+        assert!(!is_set("scopedTest.fresh"));
+
+        // a Vec drops its guards oldest first
+        let guards = vec![override_for_scope("scopedTest.order", 1).unwrap(), override_for_scope("scopedTest.order", 2).unwrap()];
+        assert_eq!(Some(2), get_int64("scopedTest.order"));
+        drop(guards);
+        assert!(!is_set("scopedTest.order"));
+
+        let section = override_for_scope("scopedTest.nested", serde_json::json!({"a": 1, "b": 1})).unwrap();
+        let leaf = override_for_scope("scopedTest.nested.a", 2).unwrap();
+        drop(section);
+        assert_eq!(Some(2), get_int64("scopedTest.nested.a"));
+        drop(leaf);
+        assert!(!is_set("scopedTest.nested"));
+    }
+
+    #[test]
+    fn configuration_is_written_back() {
+        let _guard = lock_global_state();
+        set("writeTest.window.width", 1280).unwrap();
+        let file = env::temp_dir().join("confmap_write_test.json");
+        write_config_to(&file).unwrap();
+        let written: Map<String, Value> = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(Some(1280), written["writeTest"]["window"]["width"].as_i64());
+        #[cfg(feature = "toml")]
+        {
+            let converted = env::temp_dir().join("confmap_write_test.conf");
+            write_config_as(&converted, Format::Toml).unwrap();
+            let written = Format::Toml.parse(&fs::read_to_string(&converted).unwrap()).unwrap();
+            fs::remove_file(&converted).unwrap();
+            assert_eq!(Some(1280), written["writeTest"]["window"]["width"].as_i64());
+        }
+        fs::write(&file, "{}").unwrap();
+        assert!(safe_write_config(&file).is_err());
+        assert_eq!("{}", fs::read_to_string(&file).unwrap());
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn reload_reports_the_changed_keys() {
+        let _guard = lock_global_state();
+        let edited = add_switched_source(
+            "reload diff test",
+            Layer::Custom,
+            r#"{"reloadDiffTest": {"level": "info", "dropped": true}}"#,
+            Some(r#"{"reloadDiffTest": {"level": "debug", "extra": 1}}"#),
+        );
+        read_config();
+        edited.switch();
+        let diff = reload().unwrap();
+        drop(edited);
+        assert_eq!(vec!["reloadDiffTest.extra".to_string()], diff.added);
+        assert_eq!(vec!["reloadDiffTest.dropped".to_string()], diff.removed);
+        assert_eq!(vec!["reloadDiffTest.level".to_string()], diff.changed);
+        freeze();
+        let frozen = reload();
+        freeze::thaw();
+        assert_eq!("failed to reload configuration: the configuration is frozen", frozen.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn remote_sources_are_refreshed() {
+        let _guard = lock_global_state();
+        let remote = add_switched_source("refresh test", Layer::Remote, r#"{"refreshTest": 1}"#, Some(r#"{"refreshTest": 2}"#));
+        read_config();
+        assert_eq!(Some(1), get_int64("refreshTest"));
+        remote.switch();
+        refresh::refresh_remote();
+        drop(remote);
+        assert_eq!(Some(2), get_int64("refreshTest"));
+    }
+
+    #[test]
+    fn concurrent_publishes_are_not_lost() {
+        let _guard = lock_global_state();
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    set(&format!("atomicSwapTest.set{}", i), i).unwrap();
+                    merge_value(&format!("atomicSwapTest.merged{}", i), Value::from(i)).unwrap();
+                    set_default(&format!("atomicSwapTest.default{}", i), i).unwrap();
+                })
+            })
+            .collect();
+        for _ in 0..8 {
+            read_config();
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        for i in 0..8 {
+            assert_eq!(Some(i), get_int64(&format!("atomicSwapTest.set{}", i)));
+            assert_eq!(Some(i), get_int64(&format!("atomicSwapTest.merged{}", i)));
+            assert_eq!(Some(i), get_int64(&format!("atomicSwapTest.default{}", i)));
+        }
+    }
+
+    #[test]
+    fn notifications_arrive_in_publish_order() {
+        let _guard = lock_global_state();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let subscription = subscribe("notifyOrderTest", move |change| recorder.lock().unwrap().push((change.old.clone(), change.new.clone())));
+        let writers: Vec<_> = (0..8)
+            .map(|i| std::thread::spawn(move || (0..20).for_each(|j| set("notifyOrderTest.value", i * 100 + j).unwrap())))
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        unsubscribe(subscription);
+        overrides::replace_set("notifyOrderTest", None).unwrap();
+        let seen = seen.lock().unwrap();
+        assert!(!seen.is_empty());
+        assert!(seen.windows(2).all(|pair| pair[0].1 == pair[1].0));
+    }
+
+    #[test]
+    fn failed_reloads_keep_the_previous_configuration() {
+        let _guard = lock_global_state();
+        let flaky = add_switched_source("reload test", Layer::Custom, r#"{"reloadTest": 1}"#, None);
+        reload::reload_watched();
+        assert_eq!(Some(1), get_int64("reloadTest"));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let seen = errors.clone();
+        on_reload_error(move |error| seen.lock().unwrap().push(error.clone()));
+        flaky.switch();
+        reload::reload_watched();
+        set_reload_policy(ReloadPolicy::Panic);
+        let panicked = std::panic::catch_unwind(reload::reload_watched).is_err();
+        set_reload_policy(ReloadPolicy::KeepOld);
+        drop(flaky);
+        assert!(panicked);
+        assert_eq!(Some(1), get_int64("reloadTest"));
+        assert_eq!(vec![ReloadError { source: "reload test".to_string(), message: "parse error: unexpected end of file".to_string() }], *errors.lock().unwrap());
+    }
+
+    #[cfg(all(unix, feature = "reload_signal"))]
+    #[test]
+    fn signal_reloads_keep_the_previous_configuration_when_a_source_fails() {
+        let _guard = lock_global_state();
+        let flaky = add_switched_source("signal reload test", Layer::Custom, r#"{"signalReloadTest": 1}"#, None);
+        read_config();
+        reload_on_signal().unwrap();
+        let (failed, failures) = std::sync::mpsc::channel();
+        let failed = Mutex::new(failed);
+        on_reload_error(move |error| failed.lock().unwrap().send(error.clone()).unwrap());
+        flaky.switch();
+        signal_hook::low_level::raise(signal_hook::consts::SIGHUP).unwrap();
+        let error = failures.recv_timeout(std::time::Duration::from_secs(5));
+        set_reload_policy(ReloadPolicy::KeepOld);
+        drop(flaky);
+        let error = error.unwrap();
+        assert_eq!("signal reload test", error.source);
+        assert_eq!(Some(1), get_int64("signalReloadTest"));
+    }
+
+    #[test]
+    fn subscribers_are_notified_of_their_keys() {
+        let _guard = lock_global_state();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let seen = changes.clone();
+        let subscription = subscribe("subscribeTest.logging", move |change| seen.lock().unwrap().push(change.clone()));
+        set("subscribeTest.logging.level", "debug").unwrap();
+        set("subscribeTest.other", 1).unwrap();
+        set("subscribeTest.logging.level", "debug").unwrap();
+        unsubscribe(subscription);
+        set("subscribeTest.logging.level", "info").unwrap();
+        let changes = changes.lock().unwrap();
+        assert_eq!(1, changes.len());
+        assert_eq!(vec!["subscribeTest.logging.level".to_string()], changes[0].changed);
+        assert_eq!(None, changes[0].old);
+        assert_eq!(Some(serde_json::json!({"level": "debug"})), changes[0].new);
+    }
+
+    #[test]
+    fn addresses_are_parsed() {
+        let fixed = Fixed(serde_json::from_str(r#"{"listen": ["0.0.0.0:80", "[::1]:443"], "bind": "10.0.0.1", "bad": ["0.0.0.0:80", "nope"]}"#).unwrap());
+        assert_eq!(Some(vec!["0.0.0.0:80".parse().unwrap(), "[::1]:443".parse().unwrap()]), fixed.get_socket_addr_array("listen"));
+        assert_eq!(Some("10.0.0.1".parse().unwrap()), fixed.get_ip_addr("bind"));
+        assert_eq!(None, fixed.get_socket_addr("bind"));
+        assert_eq!(None, fixed.get_socket_addr_array("bad"));
+        let malformed = "invalid socket address syntax".to_string();
+        assert_eq!(Err(ConfigError::Malformed { key: "bind".to_string(), value: "10.0.0.1".to_string(), message: malformed.clone() }), fixed.try_get_socket_addr("bind"));
+        assert_eq!(Err(ConfigError::Malformed { key: "bad[1]".to_string(), value: "nope".to_string(), message: malformed }), fixed.try_get_socket_addr_array("bad"));
+        assert_eq!(Err(ConfigError::KeyMissing("host".to_string())), fixed.try_get_ip_addr("host"));
+        assert_eq!(Err(ConfigError::WrongType { key: "listen".to_string(), expected: "ip address", found: "array" }), fixed.try_get_ip_addr("listen"));
+    }
+
+    #[test]
+    fn big_integers_and_decimals_keep_their_precision() {
+        let fixed = Fixed(serde_json::from_str(r#"{"small": -42, "id": "340282366920938463463374607431768211455"}"#).unwrap());
+        assert_eq!(Some(-42), fixed.get_i128("small"));
+        assert_eq!(Some(u128::MAX), fixed.get_u128("id"));
+        assert_eq!(None, fixed.get_i128("id"));
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            let fixed = Fixed(serde_json::from_str(r#"{"big": 170141183460469231731687303715884105727}"#).unwrap());
+            assert_eq!(Some(i128::MAX), fixed.get_i128("big"));
+        }
+        #[cfg(feature = "rust_decimal")]
+        {
+            let fixed = Fixed(serde_json::from_str(r#"{"price": 0.10, "fee": "12.345"}"#).unwrap());
+            assert_eq!(Some("0.10".parse().unwrap()), fixed.get_decimal("price"));
+            assert_eq!(Some("12.345".parse().unwrap()), fixed.get_decimal("fee"));
+        }
+    }
+
+    #[test]
+    fn fragments_are_loaded_in_lexical_order() {
+        let dir = env::temp_dir().join(format!("confmap_conf_d_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("20-tls.json"), r#"{"fragmentTest": {"tls": true}}"#).unwrap();
+        std::fs::write(dir.join("10-base.json"), r#"{"fragmentTest": {"tls": false, "port": 80}}"#).unwrap();
+        std::fs::write(dir.join("10-base.json.bak"), r#"{"fragmentTest": {"port": 1}}"#).unwrap();
+        std::fs::write(dir.join(".hidden.json"), r#"{"fragmentTest": {"port": 2}}"#).unwrap();
+        let files = fragment_files(&dir);
+        let mut configs = Loaded::default();
+        for file in files {
+            init_lazy_configs(&mut configs, &FileSource::new(file), MergeStrategy::DeepMerge);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"tls": true, "port": 80}"#).unwrap();
+        assert_eq!(Some(&expected), configs.values().get("fragmentTest"));
+    }
+
+    #[test]
+    fn profile_and_local_files_are_merged_over_the_main_file() {
+        let _guard = lock_global_state();
+        let dir = env::temp_dir().join(format!("confmap_profile_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.json"), r#"{"profileTestDb": {"host": "localhost", "port": 5432}}"#).unwrap();
+        std::fs::write(dir.join("app.dev.json"), r#"{"profileTestDb": {"host": "dev.db"}}"#).unwrap();
+        std::fs::write(dir.join("app.local.json"), r#"{"profileTestDb": {"port": 6543}}"#).unwrap();
+        set_profile("dev");
+        let mut configs = Loaded::default();
+        read_main_file(&mut configs, dir.join("app.json"), MergeStrategy::Replace, MergeStrategy::DeepMerge);
+        set_local_overrides(false);
+        let mut without_local = Loaded::default();
+        read_main_file(&mut without_local, dir.join("app.json"), MergeStrategy::Replace, MergeStrategy::DeepMerge);
+        set_local_overrides(true);
+        set_profile("");
+        std::fs::remove_dir_all(&dir).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"host": "dev.db", "port": 6543}"#).unwrap();
+        assert_eq!(Some(&expected), configs.values().get("profileTestDb"));
+        let expected: Value = serde_json::from_str(r#"{"host": "dev.db", "port": 5432}"#).unwrap();
+        assert_eq!(Some(&expected), without_local.values().get("profileTestDb"));
+    }
+
+    #[test]
+    fn merged_maps_and_overrides_are_kept_by_read_config() {
+        let _guard = lock_global_state();
+        merge(serde_json::from_str(r#"{"mergeTestLimits": {"rps": 10, "burst": 5}}"#).unwrap(), MergeStrategy::Replace).unwrap();
+        merge_value("mergeTestLimits.rps", Value::from(50)).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"rps": 50, "burst": 5}"#).unwrap();
+        assert_eq!(Some(&expected), get("mergeTestLimits").as_ref());
+        let mut configs = Loaded::default();
+        overrides::read_overrides(&mut configs);
+        assert_eq!(Some(&expected), configs.values().get("mergeTestLimits"));
+
+        set_overrides(serde_json::from_str(r#"{"mergeTestLimits": {"burst": 1}}"#).unwrap()).unwrap();
+        merge_value("mergeTestLimits.burst", Value::from(9)).unwrap();
+        assert_eq!(Some(1), get_map("mergeTestLimits").and_then(|limits| limits.get_int64("burst")));
+        set_overrides(Map::new()).unwrap();
+    }
+
+    #[test]
+    fn defaults_which_are_not_objects_are_rejected() {
+        let _guard = lock_global_state();
+        assert!(matches!(set_defaults_from(Value::from(1)), Err(DefaultsError::NotAnObject)));
+        assert!(matches!(set_defaults_from_struct(&vec![1, 2]), Err(DefaultsError::NotAnObject)));
+        let tuple_keys: std::collections::BTreeMap<(u8, u8), u8> = [((1, 2), 3)].into_iter().collect();
+        assert!(matches!(set_defaults_from_struct(&tuple_keys), Err(DefaultsError::Serialize(_))));
+        freeze();
+        let frozen = set_defaults_from(serde_json::json!({"defaultsErrorTest": 1}));
+        freeze::thaw();
+        assert!(matches!(frozen, Err(DefaultsError::Frozen)));
+        assert!(!is_set("defaultsErrorTest"));
+    }
+
+    #[test]
+    fn a_frozen_configuration_is_not_changed() {
+        let _guard = lock_global_state();
+        merge_value("freezeTestKey", Value::from(1)).unwrap();
+        freeze();
+        let rejected = merge_value("freezeTestKey", Value::from(2));
+        read_config();
+        let read = try_read_config();
+        let disabled = disable_layer(Layer::Override);
+        let precedence_set = set_precedence(&[Layer::Default]);
+        let reset_done = reset();
+        let aliased = register_alias("freezeTestAlias", "freezeTestKey");
+        let transformed = set_key_transform(|key| key.replace("Alias", "Key"));
+        let coerced = set_coercion(true);
+        let rejecting = set_mixed_arrays(MixedArrays::Reject);
+        freeze::thaw();
+        assert_eq!(Err(FrozenError), rejected);
+        assert_eq!(Err(FrozenError), read);
+        assert_eq!(Err(FrozenError), disabled);
+        assert_eq!(Err(FrozenError), precedence_set);
+        assert_eq!(Err(FrozenError), reset_done);
+        assert_eq!(Err(FrozenError), aliased);
+        assert_eq!(Err(FrozenError), transformed);
+        assert_eq!(Err(FrozenError), coerced);
+        assert_eq!(Err(FrozenError), rejecting);
+        assert_eq!(None, get_int64("freezeTestAlias"));
+        assert_eq!(Layer::Override, precedence()[0]);
+        assert_eq!(Some(1), get_int64("freezeTestKey"));
+    }
+
+    #[test]
+    fn sub_reads_keys_relative_to_a_section() {
+        let _guard = lock_global_state();
+        let data: Map<String, Value> = serde_json::from_str(r#"{"subTestDatabase": {"host": "db", "pool": {"size": 4}}}"#).unwrap();
+        CONFIGS.rcu(|configs| {
+            let mut configs = Map::clone(configs);
+            configs.extend(data.clone());
+            configs
+        });
+        let database = sub("subTestDatabase").unwrap();
+        assert_eq!(Some("db".to_string()), database.get_string("host"));
+        assert_eq!(Some(4), database.sub("pool").and_then(|pool| pool.get_int64("size")));
+        assert!(sub("subTestMissing").is_none());
+    }
+
+    #[test]
+    fn a_snapshot_is_not_changed_by_later_merges() {
+        let _guard = lock_global_state();
+        merge_value("snapshotTestKey", Value::from(1)).unwrap();
+        let before = snapshot();
+        merge_value("snapshotTestKey", Value::from(2)).unwrap();
+        assert_eq!(Some(1), before.get_int64("snapshotTestKey"));
+        assert_eq!(Some(2), snapshot().get_int64("snapshotTestKey"));
+    }
+
+    #[test]
+    fn disabled_layers_are_left_out_until_enabled() {
+        let _guard = lock_global_state();
+        let mut loaded = Loaded::default();
+        loaded.push(Layer::File, "config.json", serde_json::from_str(r#"{"layerToggleTest": 1}"#).unwrap(), MergeStrategy::Replace);
+        loaded.push(Layer::Env, "env://APP", serde_json::from_str(r#"{"layerToggleTest": 2}"#).unwrap(), MergeStrategy::Replace);
+        loaded.publish().unwrap();
+        assert_eq!(Some(2), get_int64("layerToggleTest"));
+        disable_layer(Layer::Env).unwrap();
+        assert_eq!(Some(1), get_int64("layerToggleTest"));
+        enable_layer(Layer::Env).unwrap();
+        assert_eq!(Some(2), get_int64("layerToggleTest"));
     }
 }
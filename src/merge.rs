@@ -0,0 +1,172 @@
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::pattern::{matches, split_path};
+
+/// the array merge policies by key pattern, in the order they were set.
+type ArrayMerges = Vec<(Vec<String>, ArrayMerge)>;
+
+static ARRAY_MERGES: Lazy<Mutex<ArrayMerges>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// how the values of a source are combined with the values loaded before it.
+/// # Example
+/// ```
+/// confmap::read_config_with(confmap::MergeStrategy::DeepMerge).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// a top-level key of the source replaces the whole value loaded before it.
+    Replace,
+    /// objects present in both are merged key by key, so a source overrides only the nested keys it sets.
+    DeepMerge,
+}
+
+impl MergeStrategy {
+    pub(crate) fn merge(&self, base: &mut Map<String, Value>, other: Map<String, Value>) {
+        match self {
+            MergeStrategy::Replace => replace_top_level(base, other),
+            MergeStrategy::DeepMerge => deep_merge(base, other),
+        }
+    }
+}
+
+/// how a deep merge combines an array with the array loaded before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArrayMerge {
+    /// the later array replaces the earlier one, this is the default.
+    Replace,
+    /// the elements of the later array are appended to the earlier one.
+    Append,
+    /// objects with the same value in the given field are merged, the other elements are appended.
+    MergeByKey(String),
+}
+
+/// Set how arrays matching a key pattern are combined when sources are deep-merged.
+/// the pattern syntax is the one of mark_secret, `**` applies to every array. the last matching pattern wins.
+/// # Example
+/// ```
+/// use confmap::ArrayMerge;
+///
+/// // an override file can add or change one server without restating the whole list
+/// confmap::set_array_merge("servers", ArrayMerge::MergeByKey("name".to_string()));
+/// confmap::set_array_merge("plugins", ArrayMerge::Append);
+/// ```
+pub fn set_array_merge(pattern: &str, array_merge: ArrayMerge) {
+    ARRAY_MERGES.lock().unwrap().push((split_path(pattern), array_merge));
+}
+
+/// merges `other` into `base` recursively: objects present in both are merged key by key,
+/// arrays are combined as set by set_array_merge and any other value of `other` replaces the one of `base`.
+pub(crate) fn deep_merge(base: &mut Map<String, Value>, other: Map<String, Value>) {
+    let array_merges = ARRAY_MERGES.lock().unwrap().clone();
+    deep_merge_at(base, other, &mut Vec::new(), &array_merges);
+}
+
+fn deep_merge_at(base: &mut Map<String, Value>, other: Map<String, Value>, path: &mut Vec<String>, array_merges: &[(Vec<String>, ArrayMerge)]) {
+    for (key, value) in other {
+        path.push(key.clone());
+        match (base.get_mut(&key), value) {
+            (Some(Value::Object(base_map)), Value::Object(other_map)) => deep_merge_at(base_map, other_map, path, array_merges),
+            (Some(Value::Array(base_arr)), Value::Array(other_arr)) => {
+                let array_merge = array_merges.iter().rev().find(|(pattern, _)| matches(pattern, path)).map(|(_, array_merge)| array_merge);
+                match array_merge {
+                    None | Some(ArrayMerge::Replace) => *base_arr = other_arr,
+                    Some(ArrayMerge::Append) => base_arr.extend(other_arr),
+                    Some(ArrayMerge::MergeByKey(field)) => merge_by_key(base_arr, other_arr, field, path, array_merges),
+                }
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+        path.pop();
+    }
+}
+
+fn merge_by_key(base: &mut Vec<Value>, other: Vec<Value>, field: &str, path: &mut Vec<String>, array_merges: &[(Vec<String>, ArrayMerge)]) {
+    for element in other {
+        let id = element.get(field).cloned();
+        let matching = id.and_then(|id| base.iter().position(|existing| existing.get(field) == Some(&id)));
+        match (matching, element) {
+            (Some(index), Value::Object(element)) => {
+                path.push(index.to_string());
+                if let Value::Object(existing) = &mut base[index] {
+                    deep_merge_at(existing, element, path, array_merges);
+                }
+                path.pop();
+            }
+            (_, element) => base.push(element),
+        }
+    }
+}
+
+/// adds the keys of `defaults` which `base` does not set, nested ones included.
+pub(crate) fn fill_missing(base: &mut Map<String, Value>, defaults: Map<String, Value>) {
+    for (key, value) in defaults {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Object(base_map)), Value::Object(defaults_map)) => fill_missing(base_map, defaults_map),
+            (Some(_), _) => {}
+            (None, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// replaces the top-level keys of `base` with the ones of `other`.
+pub(crate) fn replace_top_level(base: &mut Map<String, Value>, other: Map<String, Value>) {
+    base.extend(other);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_objects_are_merged() {
+        let mut base: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h", "port": 1}, "a": [1]}"#).unwrap();
+        let other: Map<String, Value> = serde_json::from_str(r#"{"db": {"port": 2}, "a": [2]}"#).unwrap();
+        deep_merge(&mut base, other);
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h", "port": 2}, "a": [2]}"#).unwrap();
+        assert_eq!(expected, base);
+    }
+
+    #[test]
+    fn defaults_fill_the_missing_keys() {
+        let mut base: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h"}, "a": 1}"#).unwrap();
+        fill_missing(&mut base, serde_json::from_str(r#"{"db": {"host": "localhost", "port": 5432}, "a": {"b": 2}}"#).unwrap());
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h", "port": 5432}, "a": 1}"#).unwrap();
+        assert_eq!(expected, base);
+    }
+
+    #[test]
+    fn replace_drops_the_nested_keys_of_the_base() {
+        let mut base: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h", "port": 1}}"#).unwrap();
+        MergeStrategy::Replace.merge(&mut base, serde_json::from_str(r#"{"db": {"port": 2}}"#).unwrap());
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"db": {"port": 2}}"#).unwrap();
+        assert_eq!(expected, base);
+    }
+
+    #[test]
+    fn arrays_follow_their_merge_policy() {
+        let base: Map<String, Value> = serde_json::from_str(
+            r#"{"mergeTest": {"plugins": ["a"], "servers": [{"name": "a", "port": 1}, {"name": "b", "port": 2}], "tags": ["x"]}}"#,
+        )
+        .unwrap();
+        let other: Map<String, Value> = serde_json::from_str(
+            r#"{"mergeTest": {"plugins": ["b"], "servers": [{"name": "b", "port": 3}, {"name": "c", "port": 4}], "tags": ["y"]}}"#,
+        )
+        .unwrap();
+        set_array_merge("mergeTest.plugins", ArrayMerge::Append);
+        set_array_merge("mergeTest.servers", ArrayMerge::MergeByKey("name".to_string()));
+        let mut merged = base.clone();
+        deep_merge(&mut merged, other);
+        let expected: Map<String, Value> = serde_json::from_str(
+            r#"{"mergeTest": {"plugins": ["a", "b"], "servers": [{"name": "a", "port": 1}, {"name": "b", "port": 3}, {"name": "c", "port": 4}], "tags": ["y"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(expected, merged);
+    }
+}
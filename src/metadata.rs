@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+static METADATA: Lazy<Mutex<BTreeMap<String, KeyMetadata>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// what the application knows about a key, beyond its value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyMetadata {
+    /// a human readable explanation of the key.
+    pub description: Option<String>,
+    /// whether the key belongs to the startup summary.
+    pub important: bool,
+    /// why the key should not be used anymore, e.g. `use server.port instead`.
+    pub deprecated: Option<String>,
+}
+
+impl KeyMetadata {
+    pub fn new() -> KeyMetadata {
+        KeyMetadata::default()
+    }
+
+    pub fn description(mut self, description: &str) -> KeyMetadata {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// list the key in summary().
+    pub fn important(mut self) -> KeyMetadata {
+        self.important = true;
+        self
+    }
+
+    /// mark the key deprecated, the note is shown in deprecation_report().
+    pub fn deprecated(mut self, note: &str) -> KeyMetadata {
+        self.deprecated = Some(note.to_string());
+        self
+    }
+}
+
+/// Set the metadata of a dotted key.
+/// # Example
+/// ```
+/// use confmap::KeyMetadata;
+///
+/// confmap::set_key_metadata("server.port", KeyMetadata::new().description("the port to listen on").important());
+/// ```
+pub fn set_key_metadata(key: &str, metadata: KeyMetadata) {
+    METADATA.lock().unwrap().insert(key.to_string(), metadata);
+}
+
+/// this function will return the metadata set for a key.
+pub fn key_metadata(key: &str) -> Option<KeyMetadata> {
+    METADATA.lock().unwrap().get(key).cloned()
+}
+
+/// every key with metadata, sorted by key.
+pub(crate) fn all_metadata() -> BTreeMap<String, KeyMetadata> {
+    METADATA.lock().unwrap().clone()
+}
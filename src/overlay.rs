@@ -0,0 +1,218 @@
+//! the overlay files merged after a main config file, e.g. `config.dev.json` for `config.json` with the profile `dev`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::pattern::glob_match;
+
+static PROFILE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static ROLE: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static HOST_OVERLAYS: AtomicBool = AtomicBool::new(false);
+static LOCAL_OVERRIDES: AtomicBool = AtomicBool::new(true);
+static USER_CONFIG_APP: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+/// Set the profile of the configuration, like spring profiles.
+/// read_config loads the config file, then deep-merges the file of the profile next to it if there is one,
+/// `config.dev.json` for `config.json` and the profile `dev`. an empty profile unsets it.
+/// # Example
+/// ```
+/// confmap::set_config_name("config.json");
+/// confmap::set_profile("dev");
+/// confmap::read_config();
+/// ```
+pub fn set_profile(profile: &str) {
+    *PROFILE.lock().unwrap() = Some(profile.to_string()).filter(|profile| !profile.is_empty());
+}
+
+/// Set the role of the machine, e.g. `web` or `worker`.
+/// read_config deep-merges the overlay files next to the config file whose name matches the role, after the profile file.
+/// the name of an overlay can be a glob: `config.web-*.json` is merged for the roles `web-eu` and `web-us`.
+/// an empty role unsets it.
+/// # Example
+/// ```
+/// confmap::set_role("web-eu");
+/// confmap::read_config();
+/// ```
+pub fn set_role(role: &str) {
+    *ROLE.lock().unwrap() = Some(role.to_string()).filter(|role| !role.is_empty());
+}
+
+/// Enable or disable the overlays selected by the hostname.
+/// when enabled, read_config deep-merges the overlay files whose name matches the hostname of the machine, after the role ones,
+/// e.g. `config.db-*.json` on the hosts `db-01` and `db-02`.
+/// # Example
+/// ```
+/// confmap::set_host_overlays(true);
+/// confmap::read_config();
+/// ```
+pub fn set_host_overlays(enabled: bool) {
+    HOST_OVERLAYS.store(enabled, Ordering::SeqCst);
+}
+
+/// Enable or disable the local override file, enabled by default.
+/// read_config deep-merges `config.local.json` for `config.json`, a file kept out of git by the developers, after every other overlay.
+/// # Example
+/// ```
+/// // only in debug builds
+/// confmap::set_local_overrides(cfg!(debug_assertions));
+/// ```
+pub fn set_local_overrides(enabled: bool) {
+    LOCAL_OVERRIDES.store(enabled, Ordering::SeqCst);
+}
+
+/// Overlay the config file of the user, for desktop applications with system defaults and user overrides.
+/// read_config deep-merges the file with the name set by set_config_name in the `app` folder of the platform config folder,
+/// after the config file, its overlays and the added files, before the sources:
+/// `$XDG_CONFIG_HOME/<app>/` (`~/.config/<app>/` by default) on linux, `~/Library/Application Support/<app>/` on macos
+/// and `%APPDATA%\<app>\` on windows. an empty app disables it.
+/// # Example
+/// ```
+/// confmap::set_config_name("config.json");
+/// confmap::set_user_config("myapp");
+/// confmap::read_config();
+/// ```
+pub fn set_user_config(app: &str) {
+    *USER_CONFIG_APP.lock().unwrap() = Some(app.to_string()).filter(|app| !app.is_empty());
+}
+
+/// the config file of the user if set_user_config enabled it and it exists.
+pub(crate) fn user_config_file(config_name: &str) -> Option<PathBuf> {
+    let app = USER_CONFIG_APP.lock().unwrap().clone()?;
+    let file = platform_config_dir(|name| env::var(name).ok())?.join(app).join(config_name);
+    Some(file).filter(|file| !config_name.is_empty() && file.is_file())
+}
+
+/// the config folder of the user on this platform, `var` reads an environment variable.
+fn platform_config_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        var("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+    }
+}
+
+/// the existing overlay files of a main config file, in the order they are merged.
+pub(crate) fn overlay_files(main: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(profile) = PROFILE.lock().unwrap().as_ref() {
+        files.push(overlay_path(main, profile));
+    }
+    files.retain(|file| file.is_file());
+    if let Some(role) = ROLE.lock().unwrap().as_ref() {
+        files.extend(matching_overlays(main, role));
+    }
+    if HOST_OVERLAYS.load(Ordering::SeqCst) {
+        match hostname() {
+            Some(hostname) => files.extend(matching_overlays(main, &hostname)),
+            None => println!("failed to get the hostname, host overlays are skipped"),
+        }
+    }
+    let mut seen = Vec::new();
+    files.retain(|file| {
+        let first = !seen.contains(file);
+        seen.push(file.clone());
+        first
+    });
+    if LOCAL_OVERRIDES.load(Ordering::SeqCst) {
+        let local = overlay_path(main, "local");
+        files.retain(|file| file != &local);
+        if local.is_file() {
+            files.push(local);
+        }
+    }
+    files
+}
+
+/// the overlay files next to `main` whose name, which can be a glob, matches `selector`, in lexical order.
+fn matching_overlays(main: &Path, selector: &str) -> Vec<PathBuf> {
+    let dir = main.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| overlay_name(main, path).is_some_and(|name| glob_match(&name, selector)))
+        .collect();
+    files.sort();
+    files
+}
+
+/// the overlay name of a file, `web-*` for `config.web-*.json` when the main file is `config.json`.
+fn overlay_name(main: &Path, file: &Path) -> Option<String> {
+    let stem = main.file_stem()?.to_string_lossy().to_string();
+    let file_name = file.file_name()?.to_string_lossy().to_string();
+    let rest = file_name.strip_prefix(&stem)?.strip_prefix('.')?;
+    let name = match main.extension() {
+        Some(extension) => rest.strip_suffix(&format!(".{}", extension.to_string_lossy()))?,
+        None => rest,
+    };
+    Some(name.to_string()).filter(|name| !name.is_empty())
+}
+
+fn hostname() -> Option<String> {
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .or_else(|| Command::new("hostname").output().ok().map(|output| String::from_utf8_lossy(&output.stdout).to_string()))?;
+    Some(hostname.trim().to_string()).filter(|hostname| !hostname.is_empty())
+}
+
+/// the file of an overlay named `name`, inserted before the extension: `config.json` becomes `config.<name>.json`.
+fn overlay_path(main: &Path, name: &str) -> PathBuf {
+    let stem = main.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let file_name = match main.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, name, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, name),
+    };
+    main.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays_are_named_after_the_main_file() {
+        assert_eq!(PathBuf::from("/etc/app/config.dev.json"), overlay_path(Path::new("/etc/app/config.json"), "dev"));
+        assert_eq!(PathBuf::from("conf.prod"), overlay_path(Path::new("conf"), "prod"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn the_user_config_dir_follows_xdg() {
+        let vars = |xdg: Option<&str>| {
+            let xdg = xdg.map(|xdg| xdg.to_string());
+            move |name: &str| match name {
+                "XDG_CONFIG_HOME" => xdg.clone(),
+                "HOME" => Some("/home/me".to_string()),
+                _ => None,
+            }
+        };
+        assert_eq!(Some(PathBuf::from("/xdg")), platform_config_dir(vars(Some("/xdg"))));
+        assert_eq!(Some(PathBuf::from("/home/me/.config")), platform_config_dir(vars(None)));
+    }
+
+    #[test]
+    fn role_overlays_match_globs() {
+        let dir = env::temp_dir().join(format!("confmap_role_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["config.json", "config.web-*.json", "config.web-eu.json", "config.worker.json", "config.web-eu.toml"] {
+            fs::write(dir.join(file), "{}").unwrap();
+        }
+        let overlays = matching_overlays(&dir.join("config.json"), "web-eu");
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(vec![dir.join("config.web-*.json"), dir.join("config.web-eu.json")], overlays);
+    }
+}
@@ -0,0 +1,193 @@
+//! the configuration given by the application code, kept across read_config.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::diff::flatten;
+use crate::freeze::{check, FrozenError};
+use crate::keys::{normalize_key, normalize_map};
+use crate::layer::Loaded;
+use crate::merge::deep_merge;
+use crate::pattern::{insert_path, is_under, remove_path, split_path};
+use crate::{Layer, MergeStrategy};
+
+type Merged = Vec<(Map<String, Value>, MergeStrategy)>;
+
+/// the maps merged by merge and merge_value, in order.
+static MERGED: Lazy<Mutex<Merged>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// the map set by set_overrides.
+static OVERRIDES: Lazy<Mutex<Map<String, Value>>> = Lazy::new(|| Mutex::new(Map::new()));
+
+/// the values given to set, applied over every layer.
+static SET: Lazy<Mutex<Map<String, Value>>> = Lazy::new(|| Mutex::new(Map::new()));
+
+/// the dotted keys given to remove, without the ones given to set again or below another removed key.
+static REMOVED: Lazy<Mutex<BTreeSet<String>>> = Lazy::new(|| Mutex::new(BTreeSet::new()));
+
+const SET_ORIGIN: &str = "set";
+const MERGE_ORIGIN: &str = "merge";
+const OVERRIDES_ORIGIN: &str = "overrides";
+
+/// Set the overrides of the configuration, the values with the highest precedence.
+/// e.g. the values computed at startup or passed by an embedding host, they are deep-merged over every source
+/// and the maps given to merge, and replace the previous overrides. fails once the configuration is frozen.
+/// # Example
+/// ```
+/// let overrides: serde_json::Map<String, serde_json::Value> = serde_json::from_str(r#"{"workers": 4}"#).unwrap();
+/// confmap::set_overrides(overrides).unwrap();
+/// assert_eq!(Some(4), confmap::get_int64("workers"));
+/// ```
+pub fn set_overrides(overrides: Map<String, Value>) -> Result<(), FrozenError> {
+    check()?;
+    *OVERRIDES.lock().unwrap() = normalize_map(overrides);
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// Merge a map into the configuration, e.g. the config of a tenant fetched at runtime over the global one.
+/// the map belongs to the Override layer, it is kept by the next read_config and merged again after every source.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// use confmap::ConfigRead;
+///
+/// let tenant: serde_json::Map<String, serde_json::Value> = serde_json::from_str(r#"{"limits": {"rps": 50}}"#).unwrap();
+/// confmap::merge(tenant, confmap::MergeStrategy::DeepMerge).unwrap();
+/// assert_eq!(Some(50), confmap::get_map("limits").unwrap().get_int64("rps"));
+/// ```
+pub fn merge(other: Map<String, Value>, strategy: MergeStrategy) -> Result<(), FrozenError> {
+    check()?;
+    MERGED.lock().unwrap().push((normalize_map(other), strategy));
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// Merge a single value at a dotted key into the configuration, like merge.
+/// # Example
+/// ```
+/// confmap::merge_value("server.port", serde_json::Value::from(8080)).unwrap();
+/// ```
+pub fn merge_value(key: &str, value: Value) -> Result<(), FrozenError> {
+    let mut map = Map::new();
+    insert_path(&mut map, &split_path(key), value);
+    merge(map, MergeStrategy::DeepMerge)
+}
+
+/// Set the value of a dotted key at runtime, e.g. a resolved hostname or a generated instance id,
+/// so every getter sees it. the value wins over every layer and is kept by the next read_config.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set("instance.id", "i-0123").unwrap();
+/// assert_eq!(Some("i-0123".to_string()), confmap::get_string("instance.id"));
+/// ```
+pub fn set(key: &str, value: impl Into<Value>) -> Result<(), FrozenError> {
+    replace_set(key, Some(value.into())).map(|_| ())
+}
+
+/// sets or, given None, unsets the value given to set at a dotted key, returning the previous one.
+pub(crate) fn replace_set(key: &str, value: Option<Value>) -> Result<Option<Value>, FrozenError> {
+    check()?;
+    let key = normalize_key(key);
+    let previous = {
+        let mut set = SET.lock().unwrap();
+        let previous = remove_path(&mut set, &split_path(&key));
+        if let Some(value) = value {
+            insert_path(&mut set, &split_path(&key), value);
+            // the key is given to set again, so neither it nor the keys below it are removed anymore.
+            REMOVED.lock().unwrap().retain(|removed| !is_under(removed, &key));
+        }
+        previous
+    };
+    Loaded::update(|_| {});
+    Ok(previous)
+}
+
+/// Remove a dotted key from the configuration at runtime, whichever source sets it, until the key is given to set again.
+/// removing a section removes every key under it. fails once the configuration is frozen.
+/// # Example
+/// ```
+/// confmap::set("scratch.value", 1).unwrap();
+/// confmap::remove("scratch").unwrap();
+/// assert!(!confmap::is_set("scratch.value"));
+/// ```
+pub fn remove(key: &str) -> Result<(), FrozenError> {
+    check()?;
+    let key = normalize_key(key);
+    remove_path(&mut SET.lock().unwrap(), &split_path(&key));
+    let mut removed = REMOVED.lock().unwrap();
+    if !removed.iter().any(|removed| is_under(&key, removed)) {
+        removed.retain(|removed| !is_under(removed, &key));
+        removed.insert(key);
+    }
+    drop(removed);
+    Loaded::update(|_| {});
+    Ok(())
+}
+
+/// removes the keys given to remove from the resolved configuration, then adds the values given to set.
+pub(crate) fn apply_runtime_changes(values: &mut Map<String, Value>, origins: &mut BTreeMap<String, String>) {
+    for key in REMOVED.lock().unwrap().iter() {
+        remove_path(values, &split_path(key));
+        let prefix = format!("{}.", key);
+        origins.retain(|leaf, _| leaf != key && !leaf.starts_with(&prefix));
+    }
+    let set = SET.lock().unwrap().clone();
+    for key in flatten(&set).into_keys() {
+        origins.insert(key, SET_ORIGIN.to_string());
+    }
+    deep_merge(values, set);
+}
+
+/// adds the configuration given by the code on top of the loaded sources, replacing the one added before.
+pub(crate) fn read_overrides(configs: &mut Loaded) {
+    configs.remove(Layer::Override, MERGE_ORIGIN);
+    configs.remove(Layer::Override, OVERRIDES_ORIGIN);
+    for (values, strategy) in MERGED.lock().unwrap().iter() {
+        configs.push(Layer::Override, MERGE_ORIGIN, values.clone(), *strategy);
+    }
+    let overrides = OVERRIDES.lock().unwrap().clone();
+    if !overrides.is_empty() {
+        configs.push(Layer::Override, OVERRIDES_ORIGIN, overrides, MergeStrategy::DeepMerge);
+    }
+}
+
+/// forgets the overrides, the merged maps and the keys given to set and remove, see reset.
+pub(crate) fn clear() {
+    MERGED.lock().unwrap().clear();
+    OVERRIDES.lock().unwrap().clear();
+    SET.lock().unwrap().clear();
+    REMOVED.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::lock_global_state;
+    use crate::{get_int64, is_set};
+
+    #[test]
+    fn removed_keys_are_forgotten_once_set_again() {
+        let _guard = lock_global_state();
+        for i in 0..10 {
+            remove("removedTest.key").unwrap();
+            set("removedTest.key", i).unwrap();
+        }
+        remove("removedTest.section.a").unwrap();
+        remove("removedTest.section").unwrap();
+        remove("removedTest.section.b").unwrap();
+        let removed: Vec<String> = REMOVED.lock().unwrap().iter().filter(|key| key.starts_with("removedTest")).cloned().collect();
+        assert_eq!(vec!["removedTest.section".to_string()], removed);
+        assert_eq!(Some(9), get_int64("removedTest.key"));
+        set("removedTest.section.servers[0]", 1).unwrap();
+        assert!(is_set("removedTest.section.servers.0"));
+        assert!(REMOVED.lock().unwrap().contains("removedTest.section"));
+        set("removedTest.section", 2).unwrap();
+        assert!(!REMOVED.lock().unwrap().contains("removedTest.section"));
+        replace_set("removedTest", None).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+//! key path patterns such as `users[*].token` or `db.*`.
+//! a path is split into segments on `.` and `[...]`, so `users[0].token` and `users.0.token` are the same path.
+//! in a pattern `*` and `?` glob inside one segment and `**` matches any number of segments.
+
+use serde_json::{Map, Value};
+
+/// splits a dotted key path into its segments.
+pub(crate) fn split_path(path: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    for c in path.chars() {
+        match c {
+            '.' | '[' | ']' => {
+                if !current.is_empty() {
+                    segments.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// the value at a path, walking objects by key and arrays by index.
+pub(crate) fn lookup_path<'a>(map: &'a Map<String, Value>, path: &[String]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let mut value = map.get(first)?;
+    for segment in rest {
+        value = match value {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(arr) => arr.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// the value of a key of the getters: the top-level key itself, or else a path through nested objects and arrays,
+/// so `database.primary.host` reads `{"database": {"primary": {"host": ...}}}` and `servers[0].host` or `ports.2` index arrays.
+pub(crate) fn lookup_key<'a>(map: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    map.get(key).or_else(|| lookup_path(map, &split_path(key)))
+}
+
+/// removes the value at a path through nested objects.
+pub(crate) fn remove_path(map: &mut Map<String, Value>, path: &[String]) -> Option<Value> {
+    let (last, parents) = path.split_last()?;
+    let mut map = map;
+    for segment in parents {
+        map = map.get_mut(segment)?.as_object_mut()?;
+    }
+    map.remove(last)
+}
+
+/// sets the value at a path, creating the objects on the way. an index into an existing array sets its element,
+/// any other value on the way is replaced with an object.
+pub(crate) fn insert_path(map: &mut Map<String, Value>, path: &[String], value: Value) {
+    if let Some((first, rest)) = path.split_first() {
+        insert_value(map.entry(first.clone()).or_insert(Value::Null), rest, value);
+    }
+}
+
+fn insert_value(target: &mut Value, path: &[String], value: Value) {
+    let Some((first, rest)) = path.split_first() else {
+        *target = value;
+        return;
+    };
+    if let Value::Array(arr) = target {
+        if let Some(element) = first.parse::<usize>().ok().and_then(|index| arr.get_mut(index)) {
+            return insert_value(element, rest, value);
+        }
+    }
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = target {
+        insert_path(map, path, value);
+    }
+}
+
+/// whether a path is another one or lies below it, e.g. `db.host` is under `db`.
+pub(crate) fn is_under(path: &str, ancestor: &str) -> bool {
+    split_path(path).starts_with(&split_path(ancestor))
+}
+
+/// whether a concrete path matches a pattern, both already split into segments.
+pub(crate) fn matches(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| matches(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => glob_match(first, segment) && matches(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// whether `path` is a pattern match or lies below one, e.g. `db.host` is covered by `db` and `db.*`.
+pub(crate) fn covers(pattern: &[String], path: &[String]) -> bool {
+    (0..=path.len()).any(|len| matches(pattern, &path[..len]))
+}
+
+/// glob matching of a single segment, `*` matches any run of characters and `?` exactly one.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_match_segments() {
+        assert_eq!(vec!["users", "0", "token"], split_path("users[0].token"));
+        assert!(matches(&split_path("users[*].token"), &split_path("users.3.token")));
+        assert!(matches(&split_path("**.password"), &split_path("db.primary.password")));
+        assert!(matches(&split_path("*_token"), &split_path("api_token")));
+        assert!(!matches(&split_path("db.*"), &split_path("db")));
+        assert!(covers(&split_path("db.*"), &split_path("db.primary.host")));
+    }
+
+    #[test]
+    fn keys_are_looked_up_by_dotted_path() {
+        let map: Map<String, Value> =
+            serde_json::from_str(r#"{"db": {"primary": {"host": "h"}}, "a.b": 1, "servers": [{"host": "s0"}], "ports": [1, 2, 3]}"#).unwrap();
+        assert_eq!(Some(&Value::from("h")), lookup_key(&map, "db.primary.host"));
+        assert_eq!(Some(&Value::from("s0")), lookup_key(&map, "servers[0].host"));
+        assert_eq!(Some(&Value::from(3)), lookup_key(&map, "ports.2"));
+        assert_eq!(None, lookup_key(&map, "ports[3]"));
+        assert_eq!(Some(&Value::from(1)), lookup_key(&map, "a.b"));
+        assert_eq!(None, lookup_key(&map, "db.primary.host.x"));
+    }
+
+    #[test]
+    fn values_are_inserted_by_path() {
+        let mut map: Map<String, Value> = serde_json::from_str(r#"{"servers": [{"host": "s0"}], "db": 1}"#).unwrap();
+        insert_path(&mut map, &split_path("servers[0].port"), Value::from(80));
+        insert_path(&mut map, &split_path("db.primary.host"), Value::from("h"));
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"servers": [{"host": "s0", "port": 80}], "db": {"primary": {"host": "h"}}}"#).unwrap();
+        assert_eq!(expected, map);
+        assert!(is_under("db.primary", "db") && is_under("db", "db") && !is_under("dbx", "db"));
+    }
+}
@@ -0,0 +1,153 @@
+//! reloading the configuration by polling its files, for filesystems without change notifications.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use crate::config_files;
+use crate::layer::Loaded;
+use crate::reload::{debounce, reload_watched};
+
+static POLLING: Mutex<Polling> = Mutex::new(Polling { interval: Duration::ZERO, running: false });
+
+/// the interval of the polling and whether its thread runs.
+struct Polling {
+    interval: Duration,
+    running: bool,
+}
+
+/// Read the configuration again when one of its files changes, checking them every `interval`.
+/// the content of the files is hashed rather than relying on their modification time, which NFS mounts
+/// and container bind mounts do not report reliably. the files are reloaded once they stay unchanged for the debounce
+/// set with set_reload_debounce, when one of them does not load the previous configuration is kept, see on_reload_error.
+/// a change has to pass the guard set with set_change_guard.
+/// the files read_config reads are checked, and the ones it looks for, so creating the file of the profile
+/// or a file in a folder added with add_config_dir reloads too.
+/// calling it again changes the interval of the running polling, a zero interval stops it.
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// confmap::read_config();
+/// confmap::watch_config_polling(Duration::from_secs(5)).expect("failed to poll the config files");
+/// ```
+pub fn watch_config_polling(interval: Duration) -> io::Result<()> {
+    let mut polling = lock_polling();
+    polling.interval = interval;
+    if interval.is_zero() || polling.running {
+        return Ok(());
+    }
+    let last = fingerprint(&watched_files());
+    thread::Builder::new().name("confmap-polling".to_string()).spawn(move || run(last))?;
+    polling.running = true;
+    Ok(())
+}
+
+fn run(mut last: u64) {
+    let _running = Running;
+    while let Some(interval) = current_interval() {
+        thread::sleep(interval);
+        if current_interval().is_some() {
+            last = poll(last);
+        }
+    }
+}
+
+/// reloads the configuration when the fingerprint of its files is no longer `last`, and returns the new one.
+fn poll(last: u64) -> u64 {
+    let mut current = fingerprint(&watched_files());
+    if current == last {
+        return last;
+    }
+    // waits for the files to settle, so a file still being written is not reloaded half-way.
+    loop {
+        thread::sleep(debounce());
+        let settled = fingerprint(&watched_files());
+        if settled == current {
+            break;
+        }
+        current = settled;
+    }
+    reload_watched();
+    fingerprint(&watched_files())
+}
+
+/// the current interval, None once the polling is stopped, which ends the thread.
+fn current_interval() -> Option<Duration> {
+    let mut polling = lock_polling();
+    if polling.interval.is_zero() {
+        polling.running = false;
+        return None;
+    }
+    Some(polling.interval)
+}
+
+fn lock_polling() -> MutexGuard<'static, Polling> {
+    POLLING.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// lets watch_config_polling start a new thread when the polling panics, e.g. under ReloadPolicy::Panic.
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            lock_polling().running = false;
+        }
+    }
+}
+
+/// the files the configuration was read from, with the ones read_config would read now.
+fn watched_files() -> Vec<PathBuf> {
+    let mut files = Loaded::current().files();
+    for file in config_files() {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+/// a hash of the paths and the content of files, a missing or unreadable file hashes as such.
+fn fingerprint(files: &[PathBuf]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for file in files {
+        file.hash(&mut hasher);
+        fs::read(file).ok().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_follows_the_content() {
+        let file = std::env::temp_dir().join("confmap_polling_test.json");
+        fs::write(&file, r#"{"workers": 1}"#).unwrap();
+        let files = vec![file.clone()];
+        let before = fingerprint(&files);
+        assert_eq!(before, fingerprint(&files));
+        fs::write(&file, r#"{"workers": 2}"#).unwrap();
+        assert_ne!(before, fingerprint(&files));
+        fs::remove_file(&file).unwrap();
+        assert_ne!(before, fingerprint(&files));
+    }
+
+    #[test]
+    fn a_zero_interval_stops_the_polling() {
+        watch_config_polling(Duration::ZERO).unwrap();
+        assert!(!lock_polling().running);
+        *lock_polling() = Polling { interval: Duration::from_secs(1), running: true };
+        assert_eq!(Some(Duration::from_secs(1)), current_interval());
+        lock_polling().interval = Duration::ZERO;
+        assert_eq!(None, current_interval());
+        assert!(!lock_polling().running);
+    }
+}
@@ -0,0 +1,65 @@
+use serde_json::{Map, Value};
+
+use crate::pattern::{covers, split_path};
+use crate::CONFIGS;
+
+/// this function will return a map with only the keys matching the given patterns,
+/// keeping their nesting, so a component gets the part of the configuration it needs and nothing else (no secrets).
+/// the patterns are the ones of mark_secret: `db.*`, `log.level`, `plugins[*].name`...
+/// # Example
+/// ```
+/// let plugin_config = confmap::project(&["db.*", "log.level"]);
+/// ```
+pub fn project(patterns: &[&str]) -> Map<String, Value> {
+    let patterns: Vec<Vec<String>> = patterns.iter().map(|pattern| split_path(pattern)).collect();
+    project_map(&CONFIGS.load(), &patterns, &mut Vec::new())
+}
+
+fn project_map(map: &Map<String, Value>, patterns: &[Vec<String>], path: &mut Vec<String>) -> Map<String, Value> {
+    let mut projected = Map::new();
+    for (key, value) in map {
+        path.push(key.clone());
+        if let Some(value) = project_value(value, patterns, path) {
+            projected.insert(key.clone(), value);
+        }
+        path.pop();
+    }
+    projected
+}
+
+fn project_value(value: &Value, patterns: &[Vec<String>], path: &mut Vec<String>) -> Option<Value> {
+    if patterns.iter().any(|pattern| covers(pattern, path)) {
+        return Some(value.clone());
+    }
+    match value {
+        Value::Object(map) => Some(project_map(map, patterns, path)).filter(|map| !map.is_empty()).map(Value::Object),
+        Value::Array(arr) => {
+            let mut projected = Vec::new();
+            for (index, element) in arr.iter().enumerate() {
+                path.push(index.to_string());
+                projected.extend(project_value(element, patterns, path));
+                path.pop();
+            }
+            Some(projected).filter(|arr| !arr.is_empty()).map(Value::Array)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_listed_patterns_are_kept() {
+        let map: Map<String, Value> = serde_json::from_str(
+            r#"{"db": {"host": "h", "password": "p"}, "log": {"level": "info", "file": "f"},
+                "plugins": [{"name": "a", "token": "t"}], "secret": "s"}"#,
+        )
+        .unwrap();
+        let patterns: Vec<Vec<String>> = ["db.host", "log.level", "plugins[*].name"].iter().map(|p| split_path(p)).collect();
+        let expected: Map<String, Value> =
+            serde_json::from_str(r#"{"db": {"host": "h"}, "log": {"level": "info"}, "plugins": [{"name": "a"}]}"#).unwrap();
+        assert_eq!(expected, project_map(&map, &patterns, &mut Vec::new()));
+    }
+}
@@ -0,0 +1,219 @@
+//! queries over the whole configuration, for the process-wide one and for a Config.
+
+use std::collections::{btree_map, BTreeMap};
+use std::sync::Arc;
+use std::vec;
+
+use serde_json::{Map, Value};
+
+use crate::diff::flatten as flatten_map;
+use crate::pattern::{matches, split_path};
+use crate::CONFIGS;
+
+/// this function will return every value whose dotted key matches a pattern, in the order of the configuration.
+/// `*` matches one segment or a part of it, `**` any number of segments, so `services.*.port`
+/// returns the port of every service without knowing their names.
+/// # Example
+/// ```
+/// for (key, port) in confmap::get_matching("services.*.port") {
+///     println!("{} = {}", key, port);
+/// }
+/// ```
+pub fn get_matching(pattern: &str) -> Vec<(String, Value)> {
+    matching(&CONFIGS.load(), pattern)
+}
+
+/// this function will return the dotted key of every value of the configuration, sorted.
+/// an array or an empty object is a value, its elements are not listed.
+/// # Example
+/// ```
+/// for key in confmap::keys() {
+///     println!("{}", key);
+/// }
+/// ```
+pub fn keys() -> Vec<String> {
+    flatten_map(&CONFIGS.load()).into_keys().collect()
+}
+
+/// this function will return a copy of the whole configuration, e.g. to dump it at startup.
+/// secrets are not masked, use redacted for anything printed.
+/// # Example
+/// ```
+/// let settings = confmap::all_settings();
+/// ```
+pub fn all_settings() -> Map<String, Value> {
+    Map::clone(&CONFIGS.load())
+}
+
+/// an iterator over the top-level `(key, value)` pairs of a configuration, in key order.
+/// it keeps the configuration it was created from, a reload does not change what it yields.
+#[derive(Debug)]
+pub struct Entries {
+    values: Arc<Map<String, Value>>,
+    keys: vec::IntoIter<String>,
+}
+
+impl Entries {
+    pub(crate) fn new(values: Arc<Map<String, Value>>) -> Entries {
+        let keys: Vec<String> = values.keys().cloned().collect();
+        Entries { values, keys: keys.into_iter() }
+    }
+}
+
+impl Iterator for Entries {
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<(String, Value)> {
+        let key = self.keys.next()?;
+        let value = self.values.get(&key).cloned().unwrap_or(Value::Null);
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+/// this function will return an iterator over the top-level `(key, value)` pairs of the configuration.
+/// # Example
+/// ```
+/// for (key, value) in confmap::iter() {
+///     println!("{} = {}", key, value);
+/// }
+/// ```
+pub fn iter() -> Entries {
+    Entries::new(CONFIGS.load_full())
+}
+
+/// this function will return an iterator over the `(dotted key, value)` pairs of every value of the configuration, see keys.
+/// # Example
+/// ```
+/// for (key, value) in confmap::iter_flat() {
+///     println!("{} = {}", key, value);
+/// }
+/// ```
+pub fn iter_flat() -> btree_map::IntoIter<String, Value> {
+    flatten_map(&CONFIGS.load()).into_iter()
+}
+
+/// this function will return every value under a prefix by dotted key, as strings,
+/// for property files, launch environments or metrics labels. strings are given as they are, other values as json.
+/// `db` and `db.` both take `db.host` and `db.port`, an empty prefix takes every value.
+/// # Example
+/// ```
+/// for (key, value) in confmap::flatten("database") {
+///     println!("{}={}", key, value);
+/// }
+/// ```
+pub fn flatten(prefix: &str) -> BTreeMap<String, String> {
+    flatten_prefix(&CONFIGS.load(), prefix)
+}
+
+pub(crate) fn flatten_prefix(map: &Map<String, Value>, prefix: &str) -> BTreeMap<String, String> {
+    let prefix = prefix.trim_end_matches('.');
+    flatten_map(map)
+        .into_iter()
+        .filter(|(key, _)| prefix.is_empty() || key == prefix || key.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.')))
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            (key, value)
+        })
+        .collect()
+}
+
+/// this function will return the dotted key of every value of the configuration equal to the given one,
+/// sections and array elements included, e.g. to find out where an endpoint or a port is configured.
+/// # Example
+/// ```
+/// for key in confmap::find_keys_with_value(&serde_json::Value::from(5432)) {
+///     println!("{} is 5432", key);
+/// }
+/// ```
+pub fn find_keys_with_value(value: &Value) -> Vec<String> {
+    keys_with_value(&CONFIGS.load(), value)
+}
+
+/// this function will return the dotted key of every value of the configuration containing a substring, sorted.
+/// strings are searched as they are, other values as json, like flatten gives them.
+/// # Example
+/// ```
+/// for key in confmap::search_keys("db.internal") {
+///     println!("{} = {:?}", key, confmap::get(&key));
+/// }
+/// ```
+pub fn search_keys(substring: &str) -> Vec<String> {
+    flatten_prefix(&CONFIGS.load(), "").into_iter().filter(|(_, value)| value.contains(substring)).map(|(key, _)| key).collect()
+}
+
+pub(crate) fn keys_with_value(map: &Map<String, Value>, value: &Value) -> Vec<String> {
+    collect(map, &|_, candidate| candidate == value).into_iter().map(|(key, _)| key).collect()
+}
+
+pub(crate) fn matching(map: &Map<String, Value>, pattern: &str) -> Vec<(String, Value)> {
+    let pattern = split_path(pattern);
+    collect(map, &|path, _| matches(&pattern, path))
+}
+
+/// every value of a map, nested ones included, whose path and value are accepted by `accept`, in the order of the map.
+fn collect(map: &Map<String, Value>, accept: &dyn Fn(&[String], &Value) -> bool) -> Vec<(String, Value)> {
+    let mut found = Vec::new();
+    for (key, value) in map {
+        collect_into(value, &mut vec![key.clone()], accept, &mut found);
+    }
+    found
+}
+
+fn collect_into(value: &Value, path: &mut Vec<String>, accept: &dyn Fn(&[String], &Value) -> bool, found: &mut Vec<(String, Value)>) {
+    if accept(path, value) {
+        found.push((path.join("."), value.clone()));
+    }
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(key, value)| (key.clone(), value)).collect(),
+        Value::Array(arr) => arr.iter().enumerate().map(|(index, value)| (index.to_string(), value)).collect(),
+        _ => return,
+    };
+    for (segment, child) in children {
+        path.push(segment);
+        collect_into(child, path, accept, found);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_yielded_in_key_order() {
+        let map: Map<String, Value> = serde_json::from_str(r#"{"b": {"c": 1}, "a": 2}"#).unwrap();
+        let entries: Vec<(String, Value)> = Entries::new(Arc::new(map)).collect();
+        assert_eq!(vec![("a".to_string(), Value::from(2)), ("b".to_string(), serde_json::json!({"c": 1}))], entries);
+    }
+
+    #[test]
+    fn values_under_a_prefix_are_flattened_to_strings() {
+        let map: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h", "ports": [1, 2]}, "dbx": 1}"#).unwrap();
+        let expected: BTreeMap<String, String> =
+            [("db.host", "h"), ("db.ports", "[1,2]")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(expected, flatten_prefix(&map, "db."));
+        assert_eq!(3, flatten_prefix(&map, "").len());
+    }
+
+    #[test]
+    fn keys_are_found_by_value() {
+        let map: Map<String, Value> = serde_json::from_str(r#"{"db": {"port": 5432}, "replicas": [{"port": 5432}], "x": "5432"}"#).unwrap();
+        assert_eq!(vec!["db.port".to_string(), "replicas.0.port".to_string()], keys_with_value(&map, &Value::from(5432)));
+    }
+
+    #[test]
+    fn wildcard_segments_collect_values() {
+        let map: Map<String, Value> =
+            serde_json::from_str(r#"{"services": {"api": {"port": 80}, "db": {"port": 5432}, "cache": {"host": "c"}}}"#).unwrap();
+        let expected = vec![("services.api.port".to_string(), Value::from(80)), ("services.db.port".to_string(), Value::from(5432))];
+        assert_eq!(expected, matching(&map, "services.*.port"));
+        assert_eq!(expected, matching(&map, "**.port"));
+    }
+}
@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// a rate limit such as `"100/s"`, `"5000/min"` or `"20/10s"`: `count` events per `window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    pub count: u64,
+    pub window: Duration,
+}
+
+/// the error returned when a string is not a valid rate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRateError(String);
+
+impl fmt::Display for ParseRateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid rate {:?}, expected <count>/<window> such as 100/s or 5000/min", self.0)
+    }
+}
+
+impl Error for ParseRateError {}
+
+impl FromStr for Rate {
+    type Err = ParseRateError;
+
+    fn from_str(s: &str) -> Result<Rate, ParseRateError> {
+        let error = || ParseRateError(s.to_string());
+        let (count, window) = s.split_once('/').ok_or_else(error)?;
+        let count = count.trim().parse::<u64>().map_err(|_| error())?;
+        let window = window.trim();
+        let unit_start = window.find(|c: char| c.is_ascii_alphabetic()).ok_or_else(error)?;
+        let (amount, unit) = window.split_at(unit_start);
+        let amount = if amount.is_empty() { 1 } else { amount.trim().parse::<u64>().map_err(|_| error())? };
+        let unit = match unit.to_ascii_lowercase().as_str() {
+            "ms" | "millisecond" | "milliseconds" => Duration::from_millis(1),
+            "s" | "sec" | "second" | "seconds" => Duration::from_secs(1),
+            "m" | "min" | "minute" | "minutes" => Duration::from_secs(60),
+            "h" | "hour" | "hours" => Duration::from_secs(60 * 60),
+            "d" | "day" | "days" => Duration::from_secs(24 * 60 * 60),
+            _ => return Err(error()),
+        };
+        let window = unit.checked_mul(u32::try_from(amount).map_err(|_| error())?).ok_or_else(error)?;
+        if count == 0 || window.is_zero() {
+            return Err(error());
+        }
+        Ok(Rate { count, window })
+    }
+}
+
+#[cfg(feature = "governor")]
+impl Rate {
+    /// converts the rate into a governor Quota allowing `count` cells per `window`, with a burst of `count`.
+    /// returns None when the count does not fit in a u32.
+    pub fn to_quota(&self) -> Option<governor::Quota> {
+        let burst = std::num::NonZeroU32::new(u32::try_from(self.count).ok()?)?;
+        governor::Quota::with_period(self.window / burst.get()).map(|quota| quota.allow_burst(burst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_are_parsed() {
+        assert_eq!(Ok(Rate { count: 100, window: Duration::from_secs(1) }), "100/s".parse());
+        assert_eq!(Ok(Rate { count: 5000, window: Duration::from_secs(60) }), "5000/min".parse());
+        assert_eq!(Ok(Rate { count: 20, window: Duration::from_secs(10) }), "20 / 10s".parse());
+        assert!("100".parse::<Rate>().is_err());
+        assert!("0/s".parse::<Rate>().is_err());
+        assert!("5/fortnight".parse::<Rate>().is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::pattern::{covers, split_path};
+use crate::CONFIGS;
+
+/// what a secret value is replaced with.
+pub const REDACTED: &str = "******";
+
+static SECRETS: Lazy<Mutex<Vec<Vec<String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Mark the keys matching a pattern as secret.
+/// a pattern is a dotted path where `[*]` or `*` matches any array element or key and `**` any depth,
+/// e.g. `db.password`, `users[*].token` or `**.api_key`. a matching object is masked as a whole.
+/// secrets are masked in redacted() and in everything confmap prints, the getters still return the real values.
+/// # Example
+/// ```
+/// confmap::mark_secret("users[*].token");
+/// confmap::mark_secret("**.password");
+/// ```
+pub fn mark_secret(pattern: &str) {
+    SECRETS.lock().unwrap().push(split_path(pattern));
+}
+
+/// this function will return a copy of the whole configuration with every secret masked.
+/// # Example
+/// ```
+/// println!("effective config: {:?}", confmap::redacted());
+/// ```
+pub fn redacted() -> Map<String, Value> {
+    redact_map(&CONFIGS.load())
+}
+
+/// masks the secrets of a map, for every path that prints or exports configuration.
+pub(crate) fn redact_map(map: &Map<String, Value>) -> Map<String, Value> {
+    let secrets = SECRETS.lock().unwrap();
+    let mut path = Vec::new();
+    mask_map(map, &secrets, &mut path)
+}
+
+fn mask_map(map: &Map<String, Value>, secrets: &[Vec<String>], path: &mut Vec<String>) -> Map<String, Value> {
+    map.iter()
+        .map(|(key, value)| {
+            path.push(key.clone());
+            let masked = mask_value(value, secrets, path);
+            path.pop();
+            (key.clone(), masked)
+        })
+        .collect()
+}
+
+fn mask_value(value: &Value, secrets: &[Vec<String>], path: &mut Vec<String>) -> Value {
+    if secrets.iter().any(|secret| covers(secret, path)) {
+        return Value::String(REDACTED.to_string());
+    }
+    match value {
+        Value::Object(map) => Value::Object(mask_map(map, secrets, path)),
+        Value::Array(arr) => Value::Array(
+            arr.iter()
+                .enumerate()
+                .map(|(index, element)| {
+                    path.push(index.to_string());
+                    let masked = mask_value(element, secrets, path);
+                    path.pop();
+                    masked
+                })
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_in_arrays_and_nested_objects_are_masked() {
+        let map: Map<String, Value> = serde_json::from_str(
+            r#"{"users": [{"name": "a", "token": "t1"}, {"name": "b", "token": "t2"}], "db": {"primary": {"password": "p"}}}"#,
+        )
+        .unwrap();
+        let secrets = vec![split_path("users[*].token"), split_path("**.password")];
+        let masked = mask_map(&map, &secrets, &mut Vec::new());
+        let expected: Map<String, Value> = serde_json::from_str(
+            r#"{"users": [{"name": "a", "token": "******"}, {"name": "b", "token": "******"}], "db": {"primary": {"password": "******"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(expected, masked);
+    }
+}
@@ -0,0 +1,105 @@
+//! refreshing the remote sources on an interval.
+
+use std::io;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use crate::layer::Loaded;
+use crate::reload::{report, ReloadError};
+use crate::source::SOURCES;
+use crate::{guard, load_checked, Layer, CONFIGS};
+
+static REFRESH: Mutex<Refresh> = Mutex::new(Refresh { interval: Duration::ZERO, running: false });
+
+/// the interval of the refresh and whether its thread runs.
+struct Refresh {
+    interval: Duration,
+    running: bool,
+}
+
+/// Load the sources of the Remote layer again every `interval`, so the configuration they provide stays fresh
+/// without the application writing its own polling loop. the files and the other sources are not read again.
+/// S3Source and KvSource send conditional requests, so an unchanged document or prefix is not downloaded again,
+/// the other sources are loaded in full every time.
+/// a source failing to load keeps its previous values (see set_reload_policy), and a change has to pass the guard
+/// set with set_change_guard. calling it again changes the interval of the running refresh, a zero interval stops it.
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// confmap::read_config();
+/// confmap::set_refresh_interval(Duration::from_secs(30)).expect("failed to start the config refresh");
+/// ```
+pub fn set_refresh_interval(interval: Duration) -> io::Result<()> {
+    let mut refresh = lock_refresh();
+    refresh.interval = interval;
+    if interval.is_zero() || refresh.running {
+        return Ok(());
+    }
+    thread::Builder::new().name("confmap-refresh".to_string()).spawn(run)?;
+    refresh.running = true;
+    Ok(())
+}
+
+fn run() {
+    let _running = Running;
+    while let Some(interval) = current_interval() {
+        thread::sleep(interval);
+        if current_interval().is_some() {
+            refresh_remote();
+        }
+    }
+}
+
+/// the current interval, None once the refresh is stopped, which ends the thread.
+fn current_interval() -> Option<Duration> {
+    let mut refresh = lock_refresh();
+    if refresh.interval.is_zero() {
+        refresh.running = false;
+        return None;
+    }
+    Some(refresh.interval)
+}
+
+fn lock_refresh() -> MutexGuard<'static, Refresh> {
+    REFRESH.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// lets set_refresh_interval start a new thread when the refresh panics, e.g. under ReloadPolicy::Panic.
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            lock_refresh().running = false;
+        }
+    }
+}
+
+/// loads the remote sources and publishes the ones whose values changed.
+pub(crate) fn refresh_remote() {
+    let sources: Vec<_> = SOURCES.lock().unwrap().iter().filter(|source| source.layer() == Layer::Remote).cloned().collect();
+    let mut refreshed: Vec<(String, Map<String, Value>)> = Vec::new();
+    for source in sources {
+        match load_checked(source.as_ref()) {
+            Ok(values) => refreshed.push((source.name(), values)),
+            Err(e) => report(&ReloadError { source: source.name(), message: e.to_string() }),
+        }
+    }
+    let mut next = Loaded::current();
+    let mut changed = false;
+    for (name, values) in &refreshed {
+        changed |= next.replace(Layer::Remote, name, values.clone());
+    }
+    if !changed || !guard::review(&CONFIGS.load(), &next.values()) {
+        return;
+    }
+    Loaded::update(|loaded| {
+        for (name, values) in refreshed {
+            loaded.replace(Layer::Remote, &name, values);
+        }
+    });
+}
@@ -0,0 +1,169 @@
+//! reloading the configuration when a watched file or source changes.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use serde_json::Map;
+
+use crate::diff::ConfigDiff;
+use crate::freeze::FrozenError;
+use crate::layer::Loaded;
+use crate::{freeze, guard, load_configs, MergeStrategy, CONFIGS};
+
+/// the callback of ReloadPolicy::Callback.
+pub type ReloadErrorHandler = Arc<dyn Fn(&ReloadError) + Send + Sync>;
+
+static POLICY: Lazy<Mutex<ReloadPolicy>> = Lazy::new(|| Mutex::new(ReloadPolicy::KeepOld));
+static DEBOUNCE: Mutex<Duration> = Mutex::new(Duration::from_millis(200));
+
+/// a file or source which could not be loaded, the reload is abandoned and the previous configuration kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadError {
+    /// the name of the file or source, as shown by summary().
+    pub source: String,
+    /// why it could not be loaded.
+    pub message: String,
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to reload {}: {}", self.source, self.message)
+    }
+}
+
+impl Error for ReloadError {}
+
+/// what happens when a reload fails because a file or source could not be loaded, see set_reload_policy.
+/// the configuration keeps the values of the last successful load unless the policy panics,
+/// so a half-written file never reaches the getters.
+#[derive(Clone, Default)]
+pub enum ReloadPolicy {
+    /// print the error and keep serving the previous configuration, the default.
+    #[default]
+    KeepOld,
+    /// panic on the thread reloading, e.g. to fail fast in staging. the watchers reload on threads of their own,
+    /// build with `panic = "abort"` to stop the process there too.
+    Panic,
+    /// give the error to a callback and keep serving the previous configuration.
+    Callback(ReloadErrorHandler),
+}
+
+impl ReloadPolicy {
+    /// a Callback policy calling `handler`.
+    pub fn callback<F>(handler: F) -> ReloadPolicy
+    where
+        F: Fn(&ReloadError) + Send + Sync + 'static,
+    {
+        ReloadPolicy::Callback(Arc::new(handler))
+    }
+}
+
+impl fmt::Debug for ReloadPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadPolicy::KeepOld => write!(f, "KeepOld"),
+            ReloadPolicy::Panic => write!(f, "Panic"),
+            ReloadPolicy::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+/// Set what happens when a reload of the watched files, the watched sources or the remote sources fails.
+/// # Example
+/// ```
+/// use confmap::ReloadPolicy;
+///
+/// let staging = std::env::var("APP_ENV").is_ok_and(|env| env == "staging");
+/// confmap::set_reload_policy(if staging { ReloadPolicy::Panic } else { ReloadPolicy::KeepOld });
+/// ```
+pub fn set_reload_policy(policy: ReloadPolicy) {
+    *POLICY.lock().unwrap() = policy;
+}
+
+/// Handle the reloads abandoned because a file or source could not be loaded, instead of printing them.
+/// this is a shorthand for `set_reload_policy(ReloadPolicy::callback(handler))`.
+/// # Example
+/// ```
+/// confmap::on_reload_error(|error| {
+///     println!("config_reload_failed source={} error={}", error.source, error.message);
+/// });
+/// ```
+pub fn on_reload_error<F>(handler: F)
+where
+    F: Fn(&ReloadError) + Send + Sync + 'static,
+{
+    set_reload_policy(ReloadPolicy::callback(handler));
+}
+
+/// Set how long the watched files have to stay unchanged before they are reloaded, 200 milliseconds by default.
+/// editors writing a temp file and renaming it, or writing a file in several chunks, would otherwise trigger several reloads.
+/// # Example
+/// ```
+/// use std::time::Duration;
+///
+/// confmap::set_reload_debounce(Duration::from_millis(500));
+/// ```
+pub fn set_reload_debounce(debounce: Duration) {
+    *DEBOUNCE.lock().unwrap() = debounce;
+}
+
+pub(crate) fn debounce() -> Duration {
+    *DEBOUNCE.lock().unwrap()
+}
+
+/// Read every file and source again now, like read_config does, and return the keys which changed,
+/// e.g. for an admin endpoint reporting what a reload did to the operator.
+/// when a file or source fails to load, or the configuration is frozen, the previous configuration is kept
+/// and the error is returned, whatever the reload policy is.
+/// # Example
+/// ```
+/// match confmap::reload() {
+///     Ok(diff) => println!("reloaded, changed keys: {:?}", diff.changed),
+///     Err(e) => println!("{}", e),
+/// }
+/// ```
+pub fn reload() -> Result<ConfigDiff, ReloadError> {
+    freeze::check().map_err(refused)?;
+    let mut next = Loaded::default();
+    load_configs(&mut next, MergeStrategy::Replace, MergeStrategy::DeepMerge);
+    if let Some(error) = next.failures().first() {
+        return Err(error.clone());
+    }
+    // the configuration it replaced, not the one before it was loaded, so the changes published meanwhile are not its own.
+    let published = next.publish().map_err(refused)?;
+    Ok(ConfigDiff::between(&published.old, &published.new))
+}
+
+fn refused(e: FrozenError) -> ReloadError {
+    ReloadError { source: "configuration".to_string(), message: e.to_string() }
+}
+
+/// read_config for a change reported by a watched file or source.
+/// every file and source has to load, and the change has to pass the change guard.
+pub(crate) fn reload_watched() {
+    let current = Map::clone(&CONFIGS.load());
+    let mut next = Loaded::default();
+    load_configs(&mut next, MergeStrategy::Replace, MergeStrategy::DeepMerge);
+    if let Some(error) = next.failures().first() {
+        report(error);
+        return;
+    }
+    if guard::review(&current, &next.values()) {
+        if let Err(e) = next.publish() {
+            report(&refused(e));
+        }
+    }
+}
+
+/// handles a failed reload as the reload policy says.
+pub(crate) fn report(error: &ReloadError) {
+    let policy = POLICY.lock().unwrap().clone();
+    match policy {
+        ReloadPolicy::KeepOld => println!("{}, keeping the previous configuration", error),
+        ReloadPolicy::Panic => panic!("{}", error),
+        ReloadPolicy::Callback(handler) => handler(error),
+    }
+}
@@ -0,0 +1,210 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use crate::import::{insert_nested, parse_scalar};
+use crate::reload::{report, ReloadError};
+use crate::{ChangeNotifier, Layer, Source, SourceError};
+
+/// how long a failed wait_for_change waits before it is tried again, doubled after every failure in a row.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// a key of a key-value store with its value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvPair {
+    /// the full key, prefix included.
+    pub key: String,
+    /// the raw value, read as a number or a boolean when it is one.
+    pub value: Vec<u8>,
+}
+
+/// the answer of a key-value store to a conditional read of a prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KvFetch {
+    /// no key under the prefix changed since the revision that was sent.
+    NotModified,
+    /// every key under the prefix with its value, and the revision of the answer.
+    Fetched { pairs: Vec<KvPair>, revision: u64 },
+}
+
+/// the etcd or consul operations KvSource needs, implement it on top of your client.
+pub trait KvClient: Send + Sync + 'static {
+    /// every key under a prefix with its value, and the revision of the answer:
+    /// the header revision of an etcd range request, or the `X-Consul-Index` of a consul kv read with `?recurse`.
+    /// when `known_revision` is given the client may answer NotModified if nothing under the prefix changed since,
+    /// e.g. when the `X-Consul-Index` of the answer is still `known_revision`. a client which cannot tell answers Fetched.
+    fn range(&self, prefix: &str, known_revision: Option<u64>) -> Result<KvFetch, SourceError>;
+
+    /// blocks until a key under the prefix changes after `revision`, then returns the new revision.
+    /// with etcd it is a watch of the prefix starting at `revision + 1`, with consul a blocking query with `?index=revision`.
+    /// returning the same revision, e.g. when a consul blocking query times out, means nothing changed.
+    fn wait_for_change(&self, prefix: &str, revision: u64) -> Result<u64, SourceError>;
+}
+
+/// a configuration stored in etcd or consul as the keys under a prefix, `/` separating the sections,
+/// e.g. with the prefix `myapp/`, the key `myapp/db/port` set to `5432` is `db.port`.
+/// watching it follows the changes pushed by the server, so they reach the configuration in seconds.
+/// a failing watch is reported like a failed reload (see set_reload_policy) and tried again after a growing delay,
+/// it ends once the source is dropped, e.g. by reset, as soon as the pending wait_for_change returns.
+/// after the first load the prefix is read conditionally, an unchanged prefix reuses the previously loaded configuration.
+/// # Example
+/// ```ignore
+/// let source = confmap::KvSource::etcd(my_etcd_client, "myapp/");
+/// confmap::add_source(Box::new(source));
+/// confmap::read_config();
+/// confmap::watch_sources()?;
+/// ```
+pub struct KvSource<C: KvClient> {
+    client: Arc<C>,
+    prefix: String,
+    store: &'static str,
+    revision: Arc<AtomicU64>,
+    cached: Mutex<Option<(u64, Map<String, Value>)>>,
+    /// ends the watch once the source is dropped.
+    stopped: Arc<AtomicBool>,
+}
+
+impl<C: KvClient> KvSource<C> {
+    /// the keys under `prefix` in etcd.
+    pub fn etcd(client: C, prefix: &str) -> KvSource<C> {
+        KvSource::new(client, prefix, "etcd")
+    }
+
+    /// the keys under `prefix` in the consul kv store.
+    pub fn consul(client: C, prefix: &str) -> KvSource<C> {
+        KvSource::new(client, prefix, "consul")
+    }
+
+    fn new(client: C, prefix: &str, store: &'static str) -> KvSource<C> {
+        KvSource { client: Arc::new(client), prefix: prefix.to_string(), store, revision: Arc::new(AtomicU64::new(0)), cached: Mutex::new(None), stopped: Arc::new(AtomicBool::new(false)) }
+    }
+}
+
+impl<C: KvClient> Source for KvSource<C> {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        let mut cached = self.cached.lock().unwrap();
+        let known = cached.as_ref().map(|(revision, _)| *revision);
+        let (pairs, revision) = match self.client.range(&self.prefix, known)? {
+            KvFetch::NotModified => match cached.as_ref() {
+                Some((_, map)) => return Ok(map.clone()),
+                None => return Err(SourceError::Other(format!("{} answered not modified to an unconditional read", self.store))),
+            },
+            KvFetch::Fetched { pairs, revision } => (pairs, revision),
+        };
+        self.revision.store(revision, Ordering::SeqCst);
+        let mut map = Map::new();
+        for pair in pairs {
+            let key = pair.key.strip_prefix(&self.prefix).unwrap_or(&pair.key);
+            let segments: Vec<&str> = key.split('/').filter(|segment| !segment.is_empty()).collect();
+            if segments.is_empty() {
+                continue;
+            }
+            insert_nested(&mut map, &segments, parse_scalar(String::from_utf8_lossy(&pair.value).trim()));
+        }
+        *cached = Some((revision, map.clone()));
+        Ok(map)
+    }
+
+    fn watch(&self, notify: ChangeNotifier) -> Result<bool, SourceError> {
+        let (client, prefix, revision, stopped) = (self.client.clone(), self.prefix.clone(), self.revision.clone(), self.stopped.clone());
+        let name = self.name();
+        thread::Builder::new().name(format!("confmap-{}-watch", self.store)).spawn(move || {
+            let mut delay = RETRY_DELAY;
+            while !stopped.load(Ordering::SeqCst) {
+                let current = revision.load(Ordering::SeqCst);
+                let answer = client.wait_for_change(&prefix, current);
+                if stopped.load(Ordering::SeqCst) {
+                    return;
+                }
+                match answer {
+                    Ok(next) => {
+                        delay = RETRY_DELAY;
+                        if next != current {
+                            revision.store(next, Ordering::SeqCst);
+                            notify();
+                        }
+                    }
+                    Err(e) => {
+                        report(&ReloadError { source: name.clone(), message: format!("failed to watch: {}", e) });
+                        thread::sleep(delay);
+                        delay = (delay * 2).min(MAX_RETRY_DELAY);
+                    }
+                }
+            }
+        })?;
+        Ok(true)
+    }
+
+    fn name(&self) -> String {
+        format!("{}: {}", self.store, self.prefix)
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Remote
+    }
+}
+
+impl<C: KvClient> Drop for KvSource<C> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+
+    use super::*;
+
+    struct FakeEtcd {
+        pairs: Vec<KvPair>,
+        changes: Mutex<Receiver<u64>>,
+        fetched: Arc<AtomicUsize>,
+    }
+
+    impl KvClient for FakeEtcd {
+        fn range(&self, _prefix: &str, known_revision: Option<u64>) -> Result<KvFetch, SourceError> {
+            if known_revision == Some(7) {
+                return Ok(KvFetch::NotModified);
+            }
+            self.fetched.fetch_add(1, Ordering::SeqCst);
+            Ok(KvFetch::Fetched { pairs: self.pairs.clone(), revision: 7 })
+        }
+
+        fn wait_for_change(&self, _prefix: &str, _revision: u64) -> Result<u64, SourceError> {
+            self.changes.lock().unwrap().recv().map_err(|e| SourceError::Other(e.to_string()))
+        }
+    }
+
+    #[test]
+    fn prefix_is_nested_and_changes_are_pushed() {
+        let pair = |key: &str, value: &str| KvPair { key: key.to_string(), value: value.as_bytes().to_vec() };
+        let (push, changes): (Sender<u64>, Receiver<u64>) = channel();
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let pairs = vec![pair("myapp/db/port", "5432"), pair("myapp/debug", "true")];
+        let etcd = FakeEtcd { pairs, changes: Mutex::new(changes), fetched: fetched.clone() };
+        let source = KvSource::etcd(etcd, "myapp/");
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"db": {"port": 5432}, "debug": true}"#).unwrap();
+        assert_eq!(expected, source.load().unwrap());
+        assert_eq!(expected, source.load().unwrap());
+        assert_eq!(1, fetched.load(Ordering::SeqCst));
+
+        let (notified, fired) = channel();
+        assert!(source.watch(Arc::new(move || notified.send(()).unwrap())).unwrap());
+        push.send(7).unwrap();
+        push.send(8).unwrap();
+        fired.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(fired.try_recv().is_err());
+        assert_eq!(8, source.revision.load(Ordering::SeqCst));
+
+        // dropping the source ends the watch without notifying the change it was waiting for
+        drop(source);
+        push.send(9).unwrap();
+        assert_eq!(Err(RecvTimeoutError::Disconnected), fired.recv_timeout(Duration::from_secs(5)));
+    }
+}
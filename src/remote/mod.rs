@@ -0,0 +1,13 @@
+//! sources for configuration kept outside the local disk.
+//! every provider talks to its backend through a small client trait,
+//! so you can plug in whichever client library your application already uses.
+
+mod kv;
+mod s3;
+mod sql;
+mod zookeeper;
+
+pub use kv::{KvClient, KvFetch, KvPair, KvSource};
+pub use s3::{ObjectFetch, ObjectStoreClient, S3Source};
+pub use sql::{SqlClient, SqlLayout, SqlSource};
+pub use zookeeper::{ZnodeClient, ZookeeperSource};
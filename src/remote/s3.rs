@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde_json::{Map, Value};
+
+use crate::{Format, Layer, Source, SourceError};
+
+/// the answer of an object store to a conditional get.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectFetch {
+    /// the object still has the ETag that was sent.
+    NotModified,
+    /// the object content with its current ETag.
+    Fetched { body: Vec<u8>, etag: Option<String> },
+}
+
+/// the object store access S3Source needs, implement it on top of your S3 client.
+/// the client is expected to pick up credentials from the standard provider chain
+/// (environment, profile, instance or container role), as the aws sdk default config does.
+pub trait ObjectStoreClient: Send + Sync + 'static {
+    /// gets an object, answering NotModified when `if_none_match` is the current ETag of the object.
+    fn get_object(&self, bucket: &str, key: &str, if_none_match: Option<&str>) -> Result<ObjectFetch, SourceError>;
+}
+
+/// a config document stored in an S3 compatible bucket, addressed as `s3://bucket/path/config.json`.
+/// the format is guessed from the object key. after the first load the object is only fetched again
+/// when its ETag changed, otherwise the previously parsed configuration is reused.
+/// # Example
+/// ```ignore
+/// let source = confmap::S3Source::new(my_s3_client, "s3://my-bucket/myapp/config.json")?;
+/// confmap::add_source(Box::new(source));
+/// confmap::read_config();
+/// ```
+pub struct S3Source<C: ObjectStoreClient> {
+    client: C,
+    bucket: String,
+    key: String,
+    format: Format,
+    cached: Mutex<Option<(String, Map<String, Value>)>>,
+}
+
+impl<C: ObjectStoreClient> S3Source<C> {
+    /// `url` is `s3://bucket/key`.
+    pub fn new(client: C, url: &str) -> Result<S3Source<C>, SourceError> {
+        let (bucket, key) = url
+            .strip_prefix("s3://")
+            .and_then(|rest| rest.split_once('/'))
+            .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+            .ok_or_else(|| SourceError::Other(format!("invalid s3 url {:?}, expected s3://bucket/key", url)))?;
+        Ok(S3Source {
+            client,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            format: Format::from_path(Path::new(key)),
+            cached: Mutex::new(None),
+        })
+    }
+
+    /// read the object as the given format whatever its key extension is.
+    pub fn format(mut self, format: Format) -> S3Source<C> {
+        self.format = format;
+        self
+    }
+}
+
+impl<C: ObjectStoreClient> Source for S3Source<C> {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        let mut cached = self.cached.lock().unwrap();
+        let etag = cached.as_ref().map(|(etag, _)| etag.as_str());
+        match self.client.get_object(&self.bucket, &self.key, etag)? {
+            ObjectFetch::NotModified => match cached.as_ref() {
+                Some((_, map)) => Ok(map.clone()),
+                None => Err(SourceError::Other("object store answered not modified to an unconditional get".to_string())),
+            },
+            ObjectFetch::Fetched { body, etag } => {
+                let map = self.format.parse(&String::from_utf8_lossy(&body))?;
+                *cached = etag.map(|etag| (etag, map.clone()));
+                Ok(map)
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.key)
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Remote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeBucket {
+        downloads: AtomicUsize,
+    }
+
+    impl ObjectStoreClient for FakeBucket {
+        fn get_object(&self, bucket: &str, key: &str, if_none_match: Option<&str>) -> Result<ObjectFetch, SourceError> {
+            assert_eq!(("bucket", "app/config.json"), (bucket, key));
+            if if_none_match == Some("v1") {
+                return Ok(ObjectFetch::NotModified);
+            }
+            self.downloads.fetch_add(1, Ordering::SeqCst);
+            Ok(ObjectFetch::Fetched { body: br#"{"port": 80}"#.to_vec(), etag: Some("v1".to_string()) })
+        }
+    }
+
+    #[test]
+    fn unchanged_object_is_not_downloaded_again() {
+        let source = S3Source::new(FakeBucket::default(), "s3://bucket/app/config.json").unwrap();
+        assert_eq!(Some(&Value::from(80)), source.load().unwrap().get("port"));
+        assert_eq!(Some(&Value::from(80)), source.load().unwrap().get("port"));
+        assert_eq!(1, source.client.downloads.load(Ordering::SeqCst));
+        assert!(S3Source::new(FakeBucket::default(), "s3://bucket").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::{Map, Value};
+
+use crate::import::insert_nested;
+use crate::{Layer, Source, SourceError};
+
+/// the database access SqlSource needs, implement it on top of your Postgres, MySQL or SQLite driver.
+pub trait SqlClient: Send + Sync + 'static {
+    /// runs the query and returns every row as the text of its columns, a NULL column is None.
+    fn query(&self, sql: &str) -> Result<Vec<Vec<Option<String>>>, SourceError>;
+}
+
+/// how the rows returned by the query of a SqlSource are turned into configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlLayout {
+    /// every row is `(key, value)`, a dotted key is a nested key and the value is read as json when it is valid json.
+    KeyValue,
+    /// every row has one json object column, the rows are merged in order.
+    JsonColumn,
+}
+
+/// a configuration loaded from a database with a configurable query.
+/// # Example
+/// ```ignore
+/// let source = confmap::SqlSource::key_value(my_pool, "SELECT key, value FROM settings WHERE tenant = 'acme'");
+/// confmap::add_source(Box::new(source));
+/// confmap::read_config();
+/// ```
+pub struct SqlSource<C: SqlClient> {
+    client: C,
+    query: String,
+    layout: SqlLayout,
+}
+
+impl<C: SqlClient> SqlSource<C> {
+    /// the query returns rows in the given layout.
+    pub fn new(client: C, query: &str, layout: SqlLayout) -> SqlSource<C> {
+        SqlSource { client, query: query.to_string(), layout }
+    }
+
+    /// the query returns `(key, value)` rows.
+    pub fn key_value(client: C, query: &str) -> SqlSource<C> {
+        SqlSource::new(client, query, SqlLayout::KeyValue)
+    }
+
+    /// the query returns rows with a single json column.
+    pub fn json_column(client: C, query: &str) -> SqlSource<C> {
+        SqlSource::new(client, query, SqlLayout::JsonColumn)
+    }
+}
+
+impl<C: SqlClient> Source for SqlSource<C> {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        let mut map = Map::new();
+        for row in self.client.query(&self.query)? {
+            match self.layout {
+                SqlLayout::KeyValue => {
+                    let (key, value) = match row.as_slice() {
+                        [Some(key), value, ..] => (key, value),
+                        _ => return Err(SourceError::Parse("expected (key, value) rows".to_string())),
+                    };
+                    let value = match value {
+                        Some(text) => serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.clone())),
+                        None => Value::Null,
+                    };
+                    insert_nested(&mut map, &key.split('.').collect::<Vec<_>>(), value);
+                }
+                SqlLayout::JsonColumn => {
+                    if let Some(Some(document)) = row.first() {
+                        let parsed: Map<String, Value> = serde_json::from_str(document)?;
+                        map.extend(parsed);
+                    }
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    fn name(&self) -> String {
+        format!("sql: {}", self.query)
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Remote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeDb(Vec<Vec<Option<String>>>);
+
+    impl SqlClient for FakeDb {
+        fn query(&self, _sql: &str) -> Result<Vec<Vec<Option<String>>>, SourceError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn row(columns: &[Option<&str>]) -> Vec<Option<String>> {
+        columns.iter().map(|column| column.map(|s| s.to_string())).collect()
+    }
+
+    #[test]
+    fn key_value_rows_are_nested_and_typed() {
+        let db = FakeDb(vec![
+            row(&[Some("db.host"), Some("db.local")]),
+            row(&[Some("db.port"), Some("5432")]),
+            row(&[Some("features"), Some(r#"["a", "b"]"#)]),
+            row(&[Some("disabled"), None]),
+        ]);
+        let expected: Map<String, Value> = serde_json::from_str(
+            r#"{"db": {"host": "db.local", "port": 5432}, "features": ["a", "b"], "disabled": null}"#,
+        )
+        .unwrap();
+        assert_eq!(expected, SqlSource::key_value(db, "SELECT key, value FROM settings").load().unwrap());
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Map, Value};
+
+use crate::import::parse_scalar;
+use crate::{ChangeNotifier, Layer, Source, SourceError};
+
+/// the ZooKeeper operations ZookeeperSource needs, implement it on top of your ZooKeeper client.
+pub trait ZnodeClient: Send + Sync + 'static {
+    /// the data of a znode.
+    fn get_data(&self, path: &str) -> Result<Vec<u8>, SourceError>;
+
+    /// the names (not the full paths) of the children of a znode.
+    fn get_children(&self, path: &str) -> Result<Vec<String>, SourceError>;
+
+    /// sets a one-shot watch on a znode, `on_change` is called once when its data or children change.
+    fn watch(&self, path: &str, on_change: Box<dyn FnOnce() + Send>) -> Result<(), SourceError>;
+}
+
+/// a configuration stored in ZooKeeper, either as one json document in a znode
+/// or as a tree of znodes where every child is a key.
+/// # Example
+/// ```ignore
+/// let source = confmap::ZookeeperSource::document(my_zk_client, "/myapp/config");
+/// confmap::add_source(Box::new(source));
+/// confmap::read_config();
+/// confmap::watch_sources()?;
+/// ```
+pub struct ZookeeperSource<C: ZnodeClient> {
+    client: Arc<C>,
+    path: String,
+    tree: bool,
+}
+
+impl<C: ZnodeClient> ZookeeperSource<C> {
+    /// the znode at `path` holds a json document.
+    pub fn document(client: C, path: &str) -> ZookeeperSource<C> {
+        ZookeeperSource { client: Arc::new(client), path: path.to_string(), tree: false }
+    }
+
+    /// every child of the znode at `path` is a key, a leaf's data is its value and a znode with children is a section.
+    pub fn tree(client: C, path: &str) -> ZookeeperSource<C> {
+        ZookeeperSource { client: Arc::new(client), path: path.to_string(), tree: true }
+    }
+
+    fn read_tree(&self, path: &str) -> Result<Map<String, Value>, SourceError> {
+        let mut map = Map::new();
+        for child in self.client.get_children(path)? {
+            let child_path = child_path(path, &child);
+            let value = if self.client.get_children(&child_path)?.is_empty() {
+                let data = self.client.get_data(&child_path)?;
+                parse_scalar(String::from_utf8_lossy(&data).trim())
+            } else {
+                Value::Object(self.read_tree(&child_path)?)
+            };
+            map.insert(child, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<C: ZnodeClient> Source for ZookeeperSource<C> {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        if self.tree {
+            self.read_tree(&self.path)
+        } else {
+            let data = self.client.get_data(&self.path)?;
+            Ok(serde_json::from_slice(&data)?)
+        }
+    }
+
+    fn watch(&self, notify: ChangeNotifier) -> Result<bool, SourceError> {
+        let watch = Arc::new(ZnodeWatch { client: self.client.clone(), root: self.path.clone(), tree: self.tree, armed: Mutex::new(BTreeSet::new()), notify });
+        watch.arm()?;
+        Ok(true)
+    }
+
+    fn name(&self) -> String {
+        format!("zookeeper: {}", self.path)
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Remote
+    }
+}
+
+/// the watches of a source, one per znode as a ZooKeeper watch only covers its own node and fires once.
+struct ZnodeWatch<C: ZnodeClient> {
+    client: Arc<C>,
+    root: String,
+    tree: bool,
+    /// the znodes with a watch which has not fired yet.
+    armed: Mutex<BTreeSet<String>>,
+    notify: ChangeNotifier,
+}
+
+impl<C: ZnodeClient> ZnodeWatch<C> {
+    /// watches every znode of the source which is not watched yet, the ones created since the last time included.
+    fn arm(self: &Arc<Self>) -> Result<(), SourceError> {
+        self.arm_node(&self.root)
+    }
+
+    fn arm_node(self: &Arc<Self>, path: &str) -> Result<(), SourceError> {
+        if self.armed.lock().unwrap().insert(path.to_string()) {
+            let (watch, fired) = (self.clone(), path.to_string());
+            if let Err(e) = self.client.watch(path, Box::new(move || watch.fired(&fired))) {
+                self.armed.lock().unwrap().remove(path);
+                return Err(e);
+            }
+        }
+        if self.tree {
+            for child in self.client.get_children(path)? {
+                self.arm_node(&child_path(path, &child))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fired(self: &Arc<Self>, path: &str) {
+        self.armed.lock().unwrap().remove(path);
+        if let Err(e) = self.arm() {
+            println!("failed to watch znodes again: {}", e);
+        }
+        (self.notify)();
+    }
+}
+
+fn child_path(path: &str, child: &str) -> String {
+    format!("{}/{}", path.trim_end_matches('/'), child)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    type Watches = Arc<Mutex<Vec<(String, Box<dyn FnOnce() + Send>)>>>;
+
+    #[derive(Default)]
+    struct FakeZk {
+        nodes: BTreeMap<String, String>,
+        watches: Watches,
+    }
+
+    impl ZnodeClient for FakeZk {
+        fn get_data(&self, path: &str) -> Result<Vec<u8>, SourceError> {
+            self.nodes.get(path).map(|data| data.clone().into_bytes()).ok_or(SourceError::Other("no node".to_string()))
+        }
+
+        fn get_children(&self, path: &str) -> Result<Vec<String>, SourceError> {
+            let prefix = format!("{}/", path);
+            Ok(self
+                .nodes
+                .keys()
+                .filter_map(|node| node.strip_prefix(&prefix))
+                .filter(|rest| !rest.contains('/'))
+                .map(|rest| rest.to_string())
+                .collect())
+        }
+
+        fn watch(&self, path: &str, on_change: Box<dyn FnOnce() + Send>) -> Result<(), SourceError> {
+            self.watches.lock().unwrap().push((path.to_string(), on_change));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tree_is_read_and_watch_rearms() {
+        let mut zk = FakeZk::default();
+        for (node, data) in [("/app", ""), ("/app/port", "8080"), ("/app/db", ""), ("/app/db/host", "db.local")] {
+            zk.nodes.insert(node.to_string(), data.to_string());
+        }
+        let watches = zk.watches.clone();
+        let source = ZookeeperSource::tree(zk, "/app");
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"port": 8080, "db": {"host": "db.local"}}"#).unwrap();
+        assert_eq!(expected, source.load().unwrap());
+
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = fired.clone();
+        assert!(source.watch(Arc::new(move || { counter.fetch_add(1, Ordering::SeqCst); })).unwrap());
+        let mut watched: Vec<String> = watches.lock().unwrap().iter().map(|(path, _)| path.clone()).collect();
+        watched.sort();
+        assert_eq!(vec!["/app", "/app/db", "/app/db/host", "/app/port"], watched);
+        // a leaf changing fires its own watch, which is armed again
+        let position = watches.lock().unwrap().iter().position(|(path, _)| path == "/app/db/host").unwrap();
+        let (_, watch) = watches.lock().unwrap().remove(position);
+        watch();
+        assert_eq!(1, fired.load(Ordering::SeqCst));
+        assert_eq!(4, watches.lock().unwrap().len());
+        assert!(watches.lock().unwrap().iter().any(|(path, _)| path == "/app/db/host"));
+    }
+}
@@ -0,0 +1,89 @@
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use crate::layer::Loaded;
+use crate::{get_as, try_get_string, ConfigError};
+
+/// the error of a required key which is missing or invalid, meant to be returned from main at startup.
+/// it tells the key, the type expected and the sources which were searched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredError {
+    /// the type expected, e.g. `u16`.
+    pub expected: &'static str,
+    /// what is wrong with the key, e.g. ConfigError::WrongType for a string given to require::<u16>.
+    pub error: ConfigError,
+    /// the names of the sources loaded, in load order.
+    pub sources: Vec<String>,
+}
+
+impl RequiredError {
+    /// the required key.
+    pub fn key(&self) -> &str {
+        self.error.key()
+    }
+}
+
+impl fmt::Display for RequiredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "required key {} ({}): {}, ", self.key(), self.expected, self.error)?;
+        if self.sources.is_empty() {
+            write!(f, "no configuration source is loaded")
+        } else {
+            write!(f, "searched {}", self.sources.join(", "))
+        }
+    }
+}
+
+impl Error for RequiredError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+fn required(expected: &'static str, error: ConfigError) -> RequiredError {
+    RequiredError { expected, error, sources: Loaded::current().origins() }
+}
+
+/// this function will return the string at a key, or an error to stop the startup with.
+/// # Example
+/// ```no_run
+/// fn main() -> Result<(), confmap::RequiredError> {
+///     confmap::read_config();
+///     let url = confmap::require_string("database.url")?;
+///     Ok(())
+/// }
+/// ```
+pub fn require_string(key: &str) -> Result<String, RequiredError> {
+    try_get_string(key).map_err(|e| required("string", e))
+}
+
+/// this function will return the value at a key deserialized into any serde type, or an error to stop the startup with.
+/// # Example
+/// ```no_run
+/// fn main() -> Result<(), confmap::RequiredError> {
+///     confmap::read_config();
+///     let port: u16 = confmap::require("server.port")?;
+///     Ok(())
+/// }
+/// ```
+pub fn require<T: DeserializeOwned>(key: &str) -> Result<T, RequiredError> {
+    get_as(key).map_err(|e| required(type_name::<T>(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_error_lists_the_sources() {
+        let error = RequiredError {
+            expected: "string",
+            error: ConfigError::KeyMissing("db.url".to_string()),
+            sources: vec!["config.json".to_string(), "env".to_string()],
+        };
+        assert_eq!("required key db.url (string): db.url is not set, searched config.json, env", error.to_string());
+    }
+}
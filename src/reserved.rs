@@ -0,0 +1,54 @@
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+static RESERVED: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Reserve a key prefix for the application code.
+/// keys starting with the prefix are dropped from everything loaded from files, environment variables or remote sources,
+/// so an external configuration cannot spoof internal control keys. `internal.` reserves the whole `internal` section.
+/// # Example
+/// ```
+/// confmap::reserve_prefix("internal.");
+/// ```
+pub fn reserve_prefix(prefix: &str) {
+    RESERVED.lock().unwrap().push(prefix.to_string());
+}
+
+/// removes the reserved keys from values loaded from the source named `origin`.
+pub(crate) fn strip_reserved(map: &mut Map<String, Value>, origin: &str) {
+    let reserved = RESERVED.lock().unwrap().clone();
+    if !reserved.is_empty() {
+        strip(map, "", &reserved, origin);
+    }
+}
+
+fn strip(map: &mut Map<String, Value>, prefix: &str, reserved: &[String], origin: &str) {
+    map.retain(|key, value| {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        if reserved.iter().any(|reserved| path.starts_with(reserved.as_str()) || path == reserved.trim_end_matches('.')) {
+            println!("ignoring reserved key {} set by {}", path, origin);
+            return false;
+        }
+        if let Value::Object(child) = value {
+            strip(child, &path, reserved, origin);
+        }
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_keys_are_removed() {
+        let mut map: Map<String, Value> =
+            serde_json::from_str(r#"{"internal": {"admin": true}, "internal_name": "x", "app": {"ctl_flag": 1, "port": 2}}"#).unwrap();
+        let reserved = vec!["internal.".to_string(), "app.ctl_".to_string()];
+        strip(&mut map, "", &reserved, "test");
+        let expected: Map<String, Value> = serde_json::from_str(r#"{"internal_name": "x", "app": {"port": 2}}"#).unwrap();
+        assert_eq!(expected, map);
+    }
+}
@@ -0,0 +1,97 @@
+use serde_json::{Map, Value};
+
+use crate::pattern::{lookup_path, split_path};
+use crate::SourceError;
+
+/// the sections of a config document that declare the types of its keys.
+const TYPE_SECTIONS: [&str; 2] = ["$types", "$schema"];
+
+/// removes the inline type declarations of a loaded document and checks the document against them.
+/// a declaration maps a dotted key to a type: `string`, `integer`, `number`, `boolean`, `array`, `object` or `null`,
+/// `[]` after a type means an array of it and a trailing `?` means the key may be missing.
+/// a `$schema` which is a string (a json schema url for editors) is only removed.
+pub(crate) fn check_inline_types(map: &mut Map<String, Value>) -> Result<(), SourceError> {
+    let mut declarations = Map::new();
+    for section in TYPE_SECTIONS {
+        if let Some(Value::Object(types)) = map.remove(section) {
+            declarations.extend(types);
+        }
+    }
+    let mut errors = Vec::new();
+    for (key, declared) in declarations {
+        let declared = match declared.as_str() {
+            Some(declared) => declared,
+            None => {
+                errors.push(format!("{}: the declared type must be a string", key));
+                continue;
+            }
+        };
+        let (expected, optional) = match declared.strip_suffix('?') {
+            Some(expected) => (expected, true),
+            None => (declared, false),
+        };
+        match lookup_path(map, &split_path(&key)) {
+            Some(value) if !is_type(value, expected) => {
+                errors.push(format!("{}: expected {}, found {}", key, expected, type_name(value)))
+            }
+            None if !optional => errors.push(format!("{}: expected {}, but it is missing", key, expected)),
+            _ => {}
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SourceError::Parse(errors.join("; ")))
+    }
+}
+
+fn is_type(value: &Value, expected: &str) -> bool {
+    if let Some(element) = expected.strip_suffix("[]") {
+        return match value {
+            Value::Array(arr) => arr.iter().all(|v| is_type(v, element)),
+            _ => false,
+        };
+    }
+    match expected {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" | "float" => value.is_number(),
+        "bool" => value.is_boolean(),
+        "any" => true,
+        _ => type_name(value) == expected,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_types_are_enforced_and_removed() {
+        let mut map: Map<String, Value> = serde_json::from_str(
+            r#"{"$schema": "https://example.com/schema.json",
+                "$types": {"port": "integer", "db.hosts": "string[]", "debug": "boolean?"},
+                "port": 8080, "db": {"hosts": ["a", "b"]}}"#,
+        )
+        .unwrap();
+        assert!(check_inline_types(&mut map).is_ok());
+        assert!(!map.contains_key("$types") && !map.contains_key("$schema"));
+
+        let mut map: Map<String, Value> =
+            serde_json::from_str(r#"{"$types": {"port": "integer", "host": "string"}, "port": "80"}"#).unwrap();
+        let error = check_inline_types(&mut map).unwrap_err().to_string();
+        assert!(error.contains("port: expected integer, found string"));
+        assert!(error.contains("host: expected string, but it is missing"));
+    }
+}
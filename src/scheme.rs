@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::{add_source, EnvSource, FileSource, Source, SourceError};
+
+/// builds the Source for a config location url, it is given the full url.
+pub type SchemeHandler = Arc<dyn Fn(&str) -> Result<Box<dyn Source>, SourceError> + Send + Sync>;
+
+static SCHEMES: Lazy<Mutex<HashMap<String, SchemeHandler>>> = Lazy::new(|| {
+    let mut schemes: HashMap<String, SchemeHandler> = HashMap::new();
+    schemes.insert(
+        "file".to_string(),
+        Arc::new(|url: &str| Ok(Box::new(FileSource::new(strip_scheme(url))) as Box<dyn Source>)),
+    );
+    schemes.insert(
+        "env".to_string(),
+        Arc::new(|url: &str| Ok(Box::new(EnvSource::new(strip_scheme(url))) as Box<dyn Source>)),
+    );
+    Mutex::new(schemes)
+});
+
+/// the scheme of a url such as `s3://bucket/key`, None for a plain path.
+pub(crate) fn scheme_of(location: &str) -> Option<&str> {
+    location
+        .split_once("://")
+        .map(|(scheme, _)| scheme)
+        .filter(|scheme| !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)))
+}
+
+pub(crate) fn strip_scheme(url: &str) -> &str {
+    url.split_once("://").map(|(_, rest)| rest).unwrap_or(url)
+}
+
+/// Register the handler building the Source for the urls of a scheme.
+/// `file://` and `env://` are registered out of the box, register `http`, `s3`... with the client your application uses.
+/// # Example
+/// ```ignore
+/// confmap::register_scheme("s3", |url| Ok(Box::new(confmap::S3Source::new(my_s3_client(), url)?)));
+/// confmap::add_config_url("s3://my-bucket/myapp/config.json")?;
+/// ```
+pub fn register_scheme<F>(scheme: &str, handler: F)
+where
+    F: Fn(&str) -> Result<Box<dyn Source>, SourceError> + Send + Sync + 'static,
+{
+    SCHEMES.lock().unwrap().insert(scheme.to_string(), Arc::new(handler));
+}
+
+/// Add a config location given as a url.
+/// the url is handed to the handler registered for its scheme and the resulting source is added like add_source does.
+/// `file:///etc/myapp/config.json` is a config file and `env://MYAPP` the environment variables prefixed by `MYAPP_`.
+/// # Example
+/// ```
+/// confmap::add_config_url("env://MYAPP").expect("env is always registered");
+/// ```
+pub fn add_config_url(url: &str) -> Result<(), SourceError> {
+    add_source(source_for_url(url)?);
+    Ok(())
+}
+
+fn source_for_url(url: &str) -> Result<Box<dyn Source>, SourceError> {
+    let scheme = scheme_of(url).ok_or_else(|| SourceError::Other(format!("{:?} is not a url", url)))?;
+    let handler = SCHEMES
+        .lock()
+        .unwrap()
+        .get(scheme)
+        .cloned()
+        .ok_or_else(|| SourceError::Other(format!("no source is registered for the scheme {:?}", scheme)))?;
+    handler(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+
+    use super::*;
+
+    struct UrlEcho(String);
+
+    impl Source for UrlEcho {
+        fn load(&self) -> Result<Map<String, Value>, SourceError> {
+            let mut map = Map::new();
+            map.insert("url".to_string(), Value::String(self.0.clone()));
+            Ok(map)
+        }
+    }
+
+    #[test]
+    fn urls_are_dispatched_by_scheme() {
+        assert_eq!(Some("s3"), scheme_of("s3://bucket/key"));
+        assert_eq!(None, scheme_of("/etc/myapp"));
+        register_scheme("echo-test", |url| Ok(Box::new(UrlEcho(url.to_string()))));
+        let source = source_for_url("echo-test://a/b").unwrap();
+        assert_eq!(Some(&Value::from("echo-test://a/b")), source.load().unwrap().get("url"));
+        assert!(source_for_url("unknown-test://a").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use serde_json::Value;
+
+use crate::getters::ReadOptions;
+use crate::keys::normalize_key;
+use crate::pattern::lookup_key;
+use crate::{ConfigRead, CONFIGS};
+
+/// a view of the configuration for one subcommand of a CLI.
+/// a key is resolved under `commands.<subcommand>` first, then globally,
+/// so every subcommand shares the top-level defaults and overrides only what it needs.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    command: String,
+}
+
+impl Scope {
+    /// the name of the subcommand this scope resolves keys for.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+}
+
+impl ConfigRead for Scope {
+    fn get(&self, key: &str) -> Option<Value> {
+        let key = &normalize_key(key);
+        let configs = CONFIGS.load();
+        configs
+            .get("commands")
+            .and_then(|commands| commands.get(&self.command))
+            .and_then(|command| command.as_object())
+            .and_then(|command| lookup_key(command, key))
+            .or_else(|| lookup_key(&configs, key))
+            .cloned()
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        ReadOptions::process()
+    }
+}
+
+/// this function will return a Scope which looks up `commands.<subcommand>.<key>` before `<key>`.
+/// # Example
+/// ```
+/// use confmap::ConfigRead;
+///
+/// let serve = confmap::scope_for_subcommand("serve");
+/// serve.get_string("bind");
+/// ```
+pub fn scope_for_subcommand(subcommand: &str) -> Scope {
+    Scope {
+        command: subcommand.to_string(),
+    }
+}
@@ -0,0 +1,135 @@
+//! overrides of a key for the lifetime of a guard, for tests of the code paths which depend on the configuration.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+
+use serde_json::Value;
+
+use crate::freeze::FrozenError;
+use crate::overrides::replace_set;
+
+/// the thread holding the scoped overrides, with the number of its guards alive.
+static OWNER: Mutex<Option<(ThreadId, usize)>> = Mutex::new(None);
+static RELEASED: Condvar = Condvar::new();
+/// the overrides of the guards, oldest first. they are restored newest first, whatever order the guards are dropped in.
+static STACK: Mutex<Vec<Scoped>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// a scoped override with what it replaced.
+struct Scoped {
+    id: u64,
+    key: String,
+    previous: Option<Value>,
+    dropped: bool,
+}
+
+/// the override of a key set by override_for_scope, the previous value is restored when it is dropped.
+#[must_use = "the override is removed as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct OverrideGuard {
+    id: u64,
+}
+
+/// Override a key until the returned guard is dropped, like set, then restore what it was.
+/// the guards of a thread wait for the guards of the other threads to be dropped, so tests run in parallel
+/// by `cargo test` do not see each other's overrides. a thread can hold several guards, an override stays
+/// until the guards created after it are dropped too, so the key is restored whatever order they are dropped in.
+/// fails once the configuration is frozen.
+/// # Example
+/// ```
+/// {
+///     let _guard = confmap::override_for_scope("feature.newCheckout", true).unwrap();
+///     assert_eq!(Some(true), confmap::get_bool("feature.newCheckout"));
+/// }
+/// assert_eq!(None, confmap::get_bool("feature.newCheckout"));
+/// ```
+pub fn override_for_scope(key: &str, value: impl Into<Value>) -> Result<OverrideGuard, FrozenError> {
+    crate::freeze::check()?;
+    acquire();
+    match replace_set(key, Some(value.into())) {
+        Ok(previous) => {
+            let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+            lock_stack().push(Scoped { id, key: key.to_string(), previous, dropped: false });
+            Ok(OverrideGuard { id })
+        }
+        Err(e) => {
+            release();
+            Err(e)
+        }
+    }
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        let restored: Vec<Scoped> = {
+            let mut stack = lock_stack();
+            if let Some(scoped) = stack.iter_mut().find(|scoped| scoped.id == self.id) {
+                scoped.dropped = true;
+            }
+            let mut restored = Vec::new();
+            while stack.last().is_some_and(|scoped| scoped.dropped) {
+                restored.extend(stack.pop());
+            }
+            restored
+        };
+        for scoped in restored {
+            if let Err(e) = replace_set(&scoped.key, scoped.previous) {
+                println!("{}, {} keeps its scoped override", e, scoped.key);
+            }
+        }
+        release();
+    }
+}
+
+fn lock_stack() -> std::sync::MutexGuard<'static, Vec<Scoped>> {
+    STACK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn acquire() {
+    let current = thread::current().id();
+    let mut owner = OWNER.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        match owner.as_mut() {
+            None => {
+                *owner = Some((current, 1));
+                return;
+            }
+            Some((thread, count)) if *thread == current => {
+                *count += 1;
+                return;
+            }
+            Some(_) => owner = RELEASED.wait(owner).unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+}
+
+fn release() {
+    let mut owner = OWNER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, count)) = owner.as_mut() {
+        *count -= 1;
+        if *count == 0 {
+            *owner = None;
+            RELEASED.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_thread_can_hold_several_guards() {
+        acquire();
+        acquire();
+        release();
+        assert!(OWNER.lock().unwrap().is_some());
+        release();
+        let handle = thread::spawn(|| {
+            acquire();
+            release();
+        });
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use serde_json::{Map, Value};
+
+use crate::getters::ReadOptions;
+use crate::pattern::lookup_key;
+use crate::ConfigRead;
+
+/// the map at a key of the configuration, returned by get_map.
+/// its typed getters read the keys relative to the section, e.g. `get_map("database")?.get_string("host")`.
+/// unlike sub it keeps the map as it is, the keys are not normalized again.
+/// the keys given to its getters are normalized, and its values converted, like the ones of the configuration it was taken from,
+/// so `get_map("server")?.get_string("Enable-TLS")` works on a case-insensitive configuration too.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigSection {
+    values: Map<String, Value>,
+    options: ReadOptions,
+}
+
+impl ConfigSection {
+    pub(crate) fn with_options(values: Map<String, Value>, options: ReadOptions) -> ConfigSection {
+        ConfigSection { values, options }
+    }
+
+    /// the whole section.
+    pub fn as_map(&self) -> &Map<String, Value> {
+        &self.values
+    }
+
+    /// the whole section, as a map.
+    pub fn into_map(self) -> Map<String, Value> {
+        self.values
+    }
+
+    /// the keys of the section.
+    pub fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl From<Map<String, Value>> for ConfigSection {
+    fn from(values: Map<String, Value>) -> Self {
+        ConfigSection { values, options: ReadOptions::default() }
+    }
+}
+
+impl From<ConfigSection> for Map<String, Value> {
+    fn from(section: ConfigSection) -> Self {
+        section.values
+    }
+}
+
+impl ConfigRead for ConfigSection {
+    fn get(&self, key: &str) -> Option<Value> {
+        lookup_key(&self.values, &self.options.keys.normalize(key)).cloned()
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        self.options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::KeyMode;
+
+    #[test]
+    fn keys_are_read_relative_to_the_section() {
+        let map: Map<String, Value> = serde_json::from_str(r#"{"host": "db", "pool": {"size": 4}}"#).unwrap();
+        let section = ConfigSection::from(map);
+        assert_eq!(Some("db".to_string()), section.get_string("host"));
+        assert_eq!(Some(4), section.get_int64("pool.size"));
+        assert_eq!(Some(4), section.get_map("pool").and_then(|pool| pool.get_int64("size")));
+        assert_eq!(None, section.get_string("Host"));
+        let lowercase = ConfigSection::with_options(section.into_map(), ReadOptions { keys: KeyMode::Lowercase, ..ReadOptions::default() });
+        assert_eq!(Some(4), lowercase.get_map("Pool").and_then(|pool| pool.get_int64("SIZE")));
+    }
+}
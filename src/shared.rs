@@ -0,0 +1,125 @@
+//! experimental: the effective configuration published to a file shared with the co-located processes.
+//! it is file-backed rather than mapped memory: the file is in `/dev/shm`, the temp folder where there is none,
+//! and holds an 8 bytes generation number, the 8 bytes length of the json document and the document.
+//! every update is written to a temp file renamed over it, so a reader sees the previous or the next document whole,
+//! even when the writer dies half-way.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::write::write_atomic;
+use crate::{Layer, Source, SourceError, CONFIGS};
+
+const HEADER_LEN: usize = 16;
+
+static SHARED_REGION: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+/// Publish the effective configuration to a shared region named `name`, now and after every read_config or reload.
+/// the co-located worker processes read it with SharedSource instead of parsing the files or polling the remotes themselves.
+/// the region is a file of `/dev/shm`, replaced whole on every update, see the module.
+/// # Example
+/// ```
+/// confmap::read_config();
+/// confmap::share_config("myapp").expect("failed to share the configuration");
+/// ```
+pub fn share_config(name: &str) -> io::Result<()> {
+    let path = region_path(name);
+    write_region(&path, &CONFIGS.load())?;
+    *SHARED_REGION.lock().unwrap() = Some(path);
+    Ok(())
+}
+
+/// writes the published configuration to the shared region, if there is one.
+pub(crate) fn republish(configs: &Map<String, Value>) {
+    if let Some(path) = SHARED_REGION.lock().unwrap().as_ref() {
+        if let Err(e) = write_region(path, configs) {
+            println!("failed to share config to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// the configuration published by share_config in another process.
+/// # Example
+/// ```
+/// confmap::add_source(Box::new(confmap::SharedSource::new("myapp")));
+/// confmap::read_config();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedSource {
+    path: PathBuf,
+}
+
+impl SharedSource {
+    pub fn new(name: &str) -> SharedSource {
+        SharedSource { path: region_path(name) }
+    }
+}
+
+impl Source for SharedSource {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        read_region(&self.path)
+    }
+
+    fn name(&self) -> String {
+        format!("shm://{}", self.path.display())
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Remote
+    }
+}
+
+fn region_path(name: &str) -> PathBuf {
+    let shm = Path::new("/dev/shm");
+    let dir = if shm.is_dir() { shm.to_path_buf() } else { std::env::temp_dir() };
+    dir.join(format!("confmap-{}", name))
+}
+
+fn write_region(path: &Path, configs: &Map<String, Value>) -> io::Result<()> {
+    let document = serde_json::to_vec(configs)?;
+    let generation = fs::read(path).ok().and_then(|region| header(&region)).map_or(0, |(generation, _)| generation) + 1;
+    let mut region = Vec::with_capacity(HEADER_LEN + document.len());
+    region.extend_from_slice(&generation.to_le_bytes());
+    region.extend_from_slice(&(document.len() as u64).to_le_bytes());
+    region.extend_from_slice(&document);
+    write_atomic(path, &region, true)
+}
+
+fn read_region(path: &Path) -> Result<Map<String, Value>, SourceError> {
+    let region = fs::read(path)?;
+    match header(&region) {
+        Some((_, len)) if region.len() - HEADER_LEN == len => Ok(serde_json::from_slice(&region[HEADER_LEN..])?),
+        _ => Err(SourceError::Other(format!("shared config {} is not a confmap region", path.display()))),
+    }
+}
+
+/// the generation and the length of the document of a region.
+fn header(region: &[u8]) -> Option<(u64, usize)> {
+    let generation = u64::from_le_bytes(region.get(..8)?.try_into().ok()?);
+    let len = u64::from_le_bytes(region.get(8..HEADER_LEN)?.try_into().ok()?);
+    Some((generation, usize::try_from(len).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_region_is_read_back() {
+        let path = std::env::temp_dir().join(format!("confmap_shared_{}", std::process::id()));
+        let configs: Map<String, Value> = serde_json::from_str(r#"{"db": {"host": "h"}, "port": 1}"#).unwrap();
+        write_region(&path, &configs).unwrap();
+        let smaller: Map<String, Value> = serde_json::from_str(r#"{"port": 2}"#).unwrap();
+        write_region(&path, &smaller).unwrap();
+        assert_eq!(smaller, read_region(&path).unwrap());
+        assert_eq!(Some((2, 10)), header(&fs::read(&path).unwrap()));
+        fs::write(&path, b"torn").unwrap();
+        assert!(read_region(&path).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
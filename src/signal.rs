@@ -0,0 +1,66 @@
+//! reading the configuration again when the process is signaled, the convention of daemons.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Read the configuration again every time the process receives SIGHUP, so `kill -HUP <pid>` reloads it.
+/// on windows ctrl+break in the console of the process does the same. like the reloads of the watched files,
+/// a file or source failing to load keeps the previous configuration (see set_reload_policy)
+/// and a change has to pass the guard set with set_change_guard.
+/// the handler is installed once, calling it again does nothing. needs the `reload_signal` feature.
+/// # Example
+/// ```
+/// confmap::read_config();
+/// confmap::reload_on_signal().expect("failed to install the reload handler");
+/// ```
+pub fn reload_on_signal() -> io::Result<()> {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let installed = install();
+    if installed.is_err() {
+        INSTALLED.store(false, Ordering::SeqCst);
+    }
+    installed
+}
+
+#[cfg(unix)]
+fn install() -> io::Result<()> {
+    use signal_hook::consts::SIGHUP;
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGHUP])?;
+    std::thread::Builder::new().name("confmap-reload".to_string()).spawn(move || {
+        for _ in signals.forever() {
+            crate::reload::reload_watched();
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn install() -> io::Result<()> {
+    use windows_sys::Win32::Foundation::{BOOL, FALSE, TRUE};
+    use windows_sys::Win32::System::Console::{SetConsoleCtrlHandler, CTRL_BREAK_EVENT};
+
+    // windows runs the handler on a thread of its own.
+    unsafe extern "system" fn handler(event: u32) -> BOOL {
+        if event != CTRL_BREAK_EVENT {
+            return FALSE;
+        }
+        crate::reload::reload_watched();
+        TRUE
+    }
+
+    if unsafe { SetConsoleCtrlHandler(Some(handler), TRUE) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn install() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "reloading on a signal is not supported on this platform"))
+}
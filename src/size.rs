@@ -0,0 +1,51 @@
+/// the number of bytes of a size such as `"10MB"`, `"512KiB"`, `"1.5G"` or `"4096"`.
+/// `K`, `M`, `G`, `T` and `P`, with or without a trailing `B`, are powers of 1000,
+/// `Ki`, `Mi`, `Gi`, `Ti` and `Pi`, with or without a trailing `B`, are powers of 1024. the units ignore case.
+pub(crate) fn parse_byte_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(unit_start);
+    let amount = amount.trim();
+    let unit = unit.to_ascii_lowercase();
+    let unit = unit.strip_suffix('b').unwrap_or(&unit);
+    let multiplier: u64 = match unit {
+        "" => 1,
+        "k" => 1000,
+        "m" => 1000u64.pow(2),
+        "g" => 1000u64.pow(3),
+        "t" => 1000u64.pow(4),
+        "p" => 1000u64.pow(5),
+        "ki" => 1 << 10,
+        "mi" => 1 << 20,
+        "gi" => 1 << 30,
+        "ti" => 1 << 40,
+        "pi" => 1 << 50,
+        _ => return None,
+    };
+    if let Ok(amount) = amount.parse::<u64>() {
+        return amount.checked_mul(multiplier);
+    }
+    let amount = amount.parse::<f64>().ok()?;
+    let bytes = (amount * multiplier as f64).round();
+    if !bytes.is_finite() || bytes < 0.0 || bytes >= u64::MAX as f64 {
+        return None;
+    }
+    Some(bytes as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_are_parsed() {
+        assert_eq!(Some(10_000_000), parse_byte_size("10MB"));
+        assert_eq!(Some(512 * 1024), parse_byte_size("512KiB"));
+        assert_eq!(Some(1_500_000_000), parse_byte_size("1.5G"));
+        assert_eq!(Some(4096), parse_byte_size("4096"));
+        assert_eq!(Some(2048), parse_byte_size("2 kib"));
+        assert_eq!(None, parse_byte_size("10 parsecs"));
+        assert_eq!(None, parse_byte_size("-1KB"));
+        assert_eq!(None, parse_byte_size("20000PB"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use crate::getters::ReadOptions;
+use crate::keys::normalize_key;
+use crate::pattern::lookup_key;
+use crate::query::Entries;
+use crate::{ConfigRead, CONFIGS};
+
+/// the configuration as it was when snapshot was called, unchanged by later reloads.
+/// its getters read the captured map directly, which suits hot loops reading many keys.
+#[derive(Debug, Clone)]
+pub struct ConfigSnapshot {
+    values: Arc<Map<String, Value>>,
+}
+
+impl ConfigSnapshot {
+    /// the whole captured configuration.
+    pub fn values(&self) -> &Map<String, Value> {
+        &self.values
+    }
+
+    /// an iterator over the top-level `(key, value)` pairs, see confmap::iter.
+    pub fn iter(&self) -> Entries {
+        Entries::new(self.values.clone())
+    }
+}
+
+impl IntoIterator for &ConfigSnapshot {
+    type Item = (String, Value);
+    type IntoIter = Entries;
+
+    fn into_iter(self) -> Entries {
+        self.iter()
+    }
+}
+
+impl ConfigRead for ConfigSnapshot {
+    fn get(&self, key: &str) -> Option<Value> {
+        lookup_key(&self.values, &normalize_key(key)).cloned()
+    }
+
+    fn read_options(&self) -> ReadOptions {
+        ReadOptions::process()
+    }
+}
+
+/// this function will return a snapshot of the current configuration, see ConfigSnapshot.
+/// # Example
+/// ```
+/// use confmap::ConfigRead;
+///
+/// let config = confmap::snapshot();
+/// for _ in 0..1000 {
+///     config.get_int64("batchSize");
+/// }
+/// ```
+pub fn snapshot() -> ConfigSnapshot {
+    ConfigSnapshot { values: CONFIGS.load_full() }
+}
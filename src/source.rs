@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::{Format, Layer};
+
+/// the callback a watching Source calls whenever its configuration changed.
+pub type ChangeNotifier = Arc<dyn Fn() + Send + Sync>;
+
+pub(crate) static SOURCES: Lazy<Mutex<Vec<Arc<dyn Source>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// the error a Source returns when it cannot produce its configuration.
+#[derive(Debug)]
+pub enum SourceError {
+    /// the underlying file or connection failed.
+    Io(io::Error),
+    /// the document was read but is not a valid configuration.
+    Parse(String),
+    /// any other failure reported by a custom source.
+    Other(String),
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::Io(e) => write!(f, "io error: {}", e),
+            SourceError::Parse(message) => write!(f, "parse error: {}", message),
+            SourceError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for SourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SourceError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SourceError {
+    fn from(e: io::Error) -> Self {
+        SourceError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SourceError {
+    fn from(e: serde_json::Error) -> Self {
+        SourceError::Parse(e.to_string())
+    }
+}
+
+/// a provider of configuration values.
+/// implement it to load configuration from anywhere (a config service, a database...)
+/// and register it with add_source, read_config merges it after the config file.
+/// # Example
+/// ```
+/// use confmap::{Source, SourceError};
+/// use serde_json::{Map, Value};
+///
+/// struct Defaults;
+///
+/// impl Source for Defaults {
+///     fn load(&self) -> Result<Map<String, Value>, SourceError> {
+///         let mut map = Map::new();
+///         map.insert("port".to_string(), Value::from(8080));
+///         Ok(map)
+///     }
+/// }
+///
+/// confmap::add_source(Box::new(Defaults));
+/// ```
+pub trait Source: Send + Sync {
+    /// reads the whole configuration this source provides.
+    fn load(&self) -> Result<Map<String, Value>, SourceError>;
+
+    /// a short name telling where the configuration comes from, shown in summary().
+    fn name(&self) -> String {
+        "custom source".to_string()
+    }
+
+    /// the layer the source belongs to, see disable_layer.
+    fn layer(&self) -> Layer {
+        Layer::Custom
+    }
+
+    /// starts watching the source, `notify` must be called every time its configuration changed.
+    /// returns false when the source cannot be watched, which is the default.
+    fn watch(&self, _notify: ChangeNotifier) -> Result<bool, SourceError> {
+        Ok(false)
+    }
+}
+
+/// a config file on the local disk, this is what read_config uses for the file set by set_config_name.
+/// the files listed in its `"$include"` key are loaded too, relative to it, and merged below its own keys.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    path: PathBuf,
+    format: Format,
+}
+
+impl FileSource {
+    /// the format is guessed from the file extension.
+    pub fn new(path: impl Into<PathBuf>) -> FileSource {
+        let path = path.into();
+        let format = Format::from_path(&path);
+        FileSource { path, format }
+    }
+
+    /// read the file as the given format whatever its extension is.
+    pub fn format(mut self, format: Format) -> FileSource {
+        self.format = format;
+        self
+    }
+}
+
+impl Source for FileSource {
+    fn load(&self) -> Result<Map<String, Value>, SourceError> {
+        crate::include::load_file(&self.path, self.format, &mut Vec::new())
+    }
+
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::File
+    }
+}
+
+/// Add a source of configuration.
+/// sources are loaded by read_config in the order they are added, after the config file.
+/// a source of a layer with a higher precedence overrides the keys of the others, see set_precedence,
+/// and within a layer a later source overrides the keys of an earlier one.
+/// # Example
+/// ```
+/// confmap::add_source(Box::new(confmap::FileSource::new("/etc/myapp/extra.json")));
+/// ```
+pub fn add_source(source: Box<dyn Source>) {
+    SOURCES.lock().unwrap().push(Arc::from(source));
+}
+
+/// Watch every added source that supports it.
+/// when a watched source reports a change the configuration is read again like read_config does,
+/// unless a file or source fails to load (see on_reload_error) or the change is rejected by the guard set with set_change_guard.
+/// # Example
+/// ```
+/// confmap::read_config();
+/// confmap::watch_sources().expect("failed to watch config sources");
+/// ```
+pub fn watch_sources() -> Result<(), SourceError> {
+    let sources = SOURCES.lock().unwrap().clone();
+    let notify: ChangeNotifier = Arc::new(crate::reload::reload_watched);
+    for source in sources {
+        source.watch(notify.clone())?;
+    }
+    Ok(())
+}
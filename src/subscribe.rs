@@ -0,0 +1,151 @@
+//! callbacks notified when the keys they subscribed to change.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::diff::changed_keys;
+use crate::keys::normalize_key;
+use crate::layer::Published;
+use crate::pattern::{covers, lookup_key, split_path};
+
+type Callback = Arc<dyn Fn(&KeyChange) + Send + Sync>;
+
+static SUBSCRIBERS: Lazy<Mutex<Vec<Subscriber>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static PENDING: Lazy<Mutex<Pending>> = Lazy::new(|| Mutex::new(Pending::default()));
+
+/// the changes published but not notified yet, oldest first, and whether a thread is notifying them.
+#[derive(Default)]
+struct Pending {
+    changes: VecDeque<Published>,
+    delivering: bool,
+}
+
+struct Subscriber {
+    id: Subscription,
+    key: String,
+    pattern: Vec<String>,
+    callback: Callback,
+}
+
+/// the handle of a subscription, give it to unsubscribe to stop the notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Subscription(u64);
+
+/// what changed under a subscribed key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyChange {
+    /// the key given to subscribe.
+    pub key: String,
+    /// the value at the key before the change, None when it was not set or the key is a pattern.
+    pub old: Option<Value>,
+    /// the value at the key after the change, None when it is not set anymore or the key is a pattern.
+    pub new: Option<Value>,
+    /// the dotted keys under the key which were added, removed or modified.
+    pub changed: Vec<String>,
+}
+
+/// Subscribe to the changes of a key and of every key under it, so a component is only notified about its own section.
+/// the key can be a pattern, see get_matching. the callback is called after the new configuration is visible to the getters,
+/// on the thread which loaded it, e.g. by read_config, set or a watched source, or on a thread still notifying an earlier change.
+/// the changes are notified one at a time in the order they were published.
+/// # Example
+/// ```
+/// let subscription = confmap::subscribe("logging.level", |change| {
+///     println!("logging.level is now {:?}", change.new);
+/// });
+/// confmap::set("logging.level", "debug").unwrap();
+/// confmap::unsubscribe(subscription);
+/// ```
+pub fn subscribe<F>(key: &str, callback: F) -> Subscription
+where
+    F: Fn(&KeyChange) + Send + Sync + 'static,
+{
+    let id = Subscription(NEXT_ID.fetch_add(1, Ordering::SeqCst));
+    let key = normalize_key(key);
+    let pattern = split_path(&key);
+    SUBSCRIBERS.lock().unwrap().push(Subscriber { id, key, pattern, callback: Arc::new(callback) });
+    id
+}
+
+/// Stop the notifications of a subscription.
+/// # Example
+/// ```
+/// let subscription = confmap::subscribe("logging", |_| {});
+/// confmap::unsubscribe(subscription);
+/// ```
+pub fn unsubscribe(subscription: Subscription) {
+    SUBSCRIBERS.lock().unwrap().retain(|subscriber| subscriber.id != subscription);
+}
+
+/// queues the change of a publish, before the next publish can run so the changes are queued in the order they are published.
+pub(crate) fn queue(published: Published) {
+    lock_pending().changes.push_back(published);
+}
+
+/// notifies the queued changes in order, unless another thread is notifying them already. a subscriber
+/// changing the configuration queues its change, which is notified after the one it is called for.
+pub(crate) fn deliver() {
+    {
+        let mut pending = lock_pending();
+        if pending.delivering {
+            return;
+        }
+        pending.delivering = true;
+    }
+    let _delivering = Delivering;
+    loop {
+        let next = {
+            let mut pending = lock_pending();
+            let next = pending.changes.pop_front();
+            pending.delivering = next.is_some();
+            next
+        };
+        let Some(published) = next else {
+            return;
+        };
+        notify(&published.old, &published.new);
+    }
+}
+
+fn lock_pending() -> MutexGuard<'static, Pending> {
+    PENDING.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// lets the next publish notify the changes left when a subscriber panics.
+struct Delivering;
+
+impl Drop for Delivering {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            lock_pending().delivering = false;
+        }
+    }
+}
+
+/// calls the subscribers of the keys which differ between two configurations.
+fn notify(old: &Map<String, Value>, new: &Map<String, Value>) {
+    let subscribers: Vec<(String, Vec<String>, Callback)> = {
+        let subscribers = SUBSCRIBERS.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+        subscribers.iter().map(|subscriber| (subscriber.key.clone(), subscriber.pattern.clone(), subscriber.callback.clone())).collect()
+    };
+    let changed = changed_keys(old, new);
+    if changed.is_empty() {
+        return;
+    }
+    for (key, pattern, callback) in subscribers {
+        let under: Vec<String> = changed.iter().filter(|changed| covers(&pattern, &split_path(changed))).cloned().collect();
+        if under.is_empty() {
+            continue;
+        }
+        callback(&KeyChange { old: lookup_key(old, &key).cloned(), new: lookup_key(new, &key).cloned(), key, changed: under });
+    }
+}
@@ -0,0 +1,36 @@
+use serde_json::Value;
+
+use crate::metadata::all_metadata;
+use crate::pattern::{lookup_path, split_path};
+use crate::layer::origin_of;
+use crate::redacted;
+
+/// this function will return a table of the keys marked important in their metadata,
+/// with their value (secrets masked) and the source it came from, to be logged once at startup.
+/// # Example
+/// ```
+/// use confmap::KeyMetadata;
+///
+/// confmap::set_key_metadata("server.port", KeyMetadata::new().important());
+/// confmap::read_config();
+/// println!("{}", confmap::summary());
+/// ```
+pub fn summary() -> String {
+    let configs = redacted();
+    let mut rows = vec![("key".to_string(), "value".to_string(), "source".to_string())];
+    for (key, _) in all_metadata().into_iter().filter(|(_, metadata)| metadata.important) {
+        let value = match lookup_path(&configs, &split_path(&key)) {
+            None => "<unset>".to_string(),
+            Some(Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        };
+        let source = origin_of(&key).unwrap_or_else(|| "-".to_string());
+        rows.push((key, value, source));
+    }
+    let key_width = rows.iter().map(|row| row.0.chars().count()).max().unwrap_or(0);
+    let value_width = rows.iter().map(|row| row.1.chars().count()).max().unwrap_or(0);
+    rows.iter()
+        .map(|(key, value, source)| format!("{:key_width$}  {:value_width$}  {}", key, value, source).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,181 @@
+//! writing the configuration back to disk.
+
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{config_name, config_path, Format, CONFIGS};
+
+/// numbers the temp files of the writes, which may run concurrently.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Write the whole configuration to the config file set by set_config_name, in the format of its extension,
+/// e.g. to persist the settings a GUI changed with set. every layer is written, defaults and environment included,
+/// like all_settings returns them.
+/// # Example
+/// ```no_run
+/// confmap::set_config_name("config.json");
+/// confmap::read_config();
+/// confmap::set("window.width", 1280).unwrap();
+/// confmap::write_config().expect("failed to save the settings");
+/// ```
+pub fn write_config() -> Result<(), Box<dyn Error>> {
+    write_config_to(main_config_file()?)
+}
+
+/// Write the whole configuration to a file, in the format of its extension, see write_config.
+/// the document is written to a temp file next to it which is then renamed over it, keeping its permissions,
+/// so a crash while writing never leaves a truncated config file behind.
+/// # Example
+/// ```no_run
+/// confmap::write_config_to("/etc/myapp/config.json").expect("failed to save the settings");
+/// ```
+pub fn write_config_to(path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    write_config_as(path, Format::from_path(path))
+}
+
+/// Write the whole configuration to a file in the given format whatever its extension is, like write_config_to,
+/// e.g. to convert a config loaded from json to toml. toml needs the `toml` feature.
+/// # Example
+/// ```no_run
+/// confmap::set_config_name("config.json");
+/// confmap::read_config();
+/// confmap::write_config_as("config.converted", confmap::Format::Json).expect("failed to convert the config");
+/// ```
+pub fn write_config_as(path: impl AsRef<Path>, format: Format) -> Result<(), Box<dyn Error>> {
+    let document = format.serialize(&CONFIGS.load())?;
+    write_atomic(path.as_ref(), document.as_bytes(), true)?;
+    Ok(())
+}
+
+/// Write the whole configuration to a file like write_config_to, but fail when the file already exists,
+/// e.g. to create the config file on the first run without overwriting the one a user edited.
+/// # Example
+/// ```no_run
+/// match confmap::safe_write_config("config.json") {
+///     Ok(()) => println!("created config.json"),
+///     Err(e) => println!("keeping the existing config: {}", e),
+/// }
+/// ```
+pub fn safe_write_config(path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let document = Format::from_path(path).serialize(&CONFIGS.load())?;
+    write_atomic(path, document.as_bytes(), false)?;
+    Ok(())
+}
+
+/// the config file set by set_config_name, where read_config looks for it.
+fn main_config_file() -> io::Result<PathBuf> {
+    let name = config_name();
+    if name.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no config file, set it with set_config_name"));
+    }
+    Ok(PathBuf::from(config_path() + &name))
+}
+
+/// writes a file through a temp file in the same folder renamed into place, so readers and crashes
+/// only ever see the old or the new content. an existing file keeps its permissions, and a symlink
+/// keeps pointing to the file, which is the one written. without `overwrite` it fails when the file exists.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8], overwrite: bool) -> io::Result<()> {
+    let path = &resolve_links(path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"))?;
+    let temp = dir.join(format!(".{}.{}.{}.tmp", name.to_string_lossy(), process::id(), TEMP_FILES.fetch_add(1, Ordering::SeqCst)));
+    let written = write_temp(&temp, path, contents).and_then(|()| {
+        if overwrite {
+            fs::rename(&temp, path)
+        } else {
+            // a hard link fails when the target exists, where a rename would replace it.
+            fs::hard_link(&temp, path).and_then(|()| fs::remove_file(&temp))
+        }
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written?;
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// follows the symlinks to the file they point to, which may not exist yet. a rename over a symlink
+/// would replace the link itself with a regular file.
+fn resolve_links(path: &Path) -> PathBuf {
+    let mut path = path.to_path_buf();
+    // as many links as linux follows before giving up on a loop.
+    for _ in 0..40 {
+        match fs::read_link(&path) {
+            Ok(target) => path = path.parent().map(|dir| dir.join(&target)).unwrap_or(target),
+            Err(_) => break,
+        }
+    }
+    path
+}
+
+fn write_temp(temp: &Path, path: &Path, contents: &[u8]) -> io::Result<()> {
+    let existing = fs::metadata(path).ok();
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    // the temp file is created with the permissions of the file, so a private config is never readable by others
+    // while it is written. the umask may remove some of them, they are set again below.
+    #[cfg(unix)]
+    if let Some(metadata) = &existing {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(metadata.permissions().mode() & 0o7777);
+    }
+    let mut file = options.open(temp)?;
+    file.write_all(contents)?;
+    if let Some(metadata) = existing {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_writes_replace_the_file_and_keep_its_permissions() {
+        let file = std::env::temp_dir().join("confmap_atomic_write_test.json");
+        let _ = fs::remove_file(&file);
+        write_atomic(&file, b"{}", false).unwrap();
+        assert!(write_atomic(&file, b"{\"a\": 1}", false).is_err());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+            write_atomic(&file, b"{\"a\": 2}", true).unwrap();
+            assert_eq!(0o600, fs::metadata(&file).unwrap().permissions().mode() & 0o777);
+        }
+        write_atomic(&file, b"{\"a\": 3}", true).unwrap();
+        assert_eq!("{\"a\": 3}", fs::read_to_string(&file).unwrap());
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_writes_go_through_symlinks() {
+        let dir = std::env::temp_dir().join("confmap_atomic_write_link_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.json");
+        let link = dir.join("current.json");
+        fs::write(&file, "{}").unwrap();
+        std::os::unix::fs::symlink("config.json", &link).unwrap();
+        write_atomic(&link, b"{\"a\": 1}", true).unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!("{\"a\": 1}", fs::read_to_string(&file).unwrap());
+        assert!(write_atomic(&link, b"{}", false).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}